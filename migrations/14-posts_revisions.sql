@@ -0,0 +1,30 @@
+CREATE TABLE `posts_revisions` (
+	`id`          int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`post_id`     int(10) unsigned NOT NULL,
+	`title`       VARCHAR(191)     NOT NULL,
+	`description` TEXT                 NULL,
+	`content`     LONGTEXT             NULL,
+	`edited_by`   VARCHAR(191)         NULL,
+	`created_at`  DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	KEY `posts_revisions_post_id_index` (`post_id`)
+);
+
+INSERT INTO `posts_revisions` (`post_id`, `title`, `description`, `content`, `created_at`)
+	SELECT `id`, `title`, `description`, `content`, `updated_at` FROM `posts_data`;
+
+-- The editor can be attributed by running `SET @revision_editor = '...'`
+-- on the same connection before the write.
+CREATE TRIGGER `posts_revisions_after_insert` AFTER INSERT ON `posts_data`
+	FOR EACH ROW
+	INSERT INTO `posts_revisions` (`post_id`, `title`, `description`, `content`, `edited_by`, `created_at`)
+		VALUES (NEW.`id`, NEW.`title`, NEW.`description`, NEW.`content`, @revision_editor, NOW());
+
+CREATE TRIGGER `posts_revisions_after_update` AFTER UPDATE ON `posts_data`
+	FOR EACH ROW
+	INSERT INTO `posts_revisions` (`post_id`, `title`, `description`, `content`, `edited_by`, `created_at`)
+		SELECT NEW.`id`, NEW.`title`, NEW.`description`, NEW.`content`, @revision_editor, NOW()
+		FROM DUAL
+		WHERE NOT (OLD.`title` <=> NEW.`title`
+			AND OLD.`description` <=> NEW.`description`
+			AND OLD.`content` <=> NEW.`content`);
@@ -0,0 +1,33 @@
+CREATE TABLE `posts_pivot_authors_data` (
+	`authors_id` int(10) unsigned NOT NULL,
+	`posts_id`   int(10) unsigned NOT NULL,
+	`position`   int(10) unsigned NOT NULL DEFAULT 0,
+	PRIMARY KEY (`authors_id`, `posts_id`),
+	KEY `posts_pivot_authors_data_posts_id_index` (`posts_id`)
+);
+
+INSERT INTO `posts_pivot_authors_data` (`authors_id`, `posts_id`, `position`)
+	SELECT `author_id`, `id`, 0 FROM `posts_data` WHERE `author_id` IS NOT NULL;
+
+-- Keep the primary author (`posts_data`.`author_id`) in the pivot as the first author.
+CREATE TRIGGER `posts_pivot_authors_data_after_insert` AFTER INSERT ON `posts_data`
+	FOR EACH ROW
+	INSERT INTO `posts_pivot_authors_data` (`authors_id`, `posts_id`, `position`)
+		SELECT NEW.`author_id`, NEW.`id`, 0 FROM DUAL WHERE NEW.`author_id` IS NOT NULL
+		ON DUPLICATE KEY UPDATE `position` = 0;
+
+-- A replaced primary author is removed, so only the new one is first.
+DELIMITER //
+CREATE TRIGGER `posts_pivot_authors_data_after_update` AFTER UPDATE ON `posts_data`
+	FOR EACH ROW
+BEGIN
+	IF NOT (OLD.`author_id` <=> NEW.`author_id`) THEN
+		DELETE FROM `posts_pivot_authors_data`
+			WHERE `posts_id` = NEW.`id` AND `authors_id` = OLD.`author_id` AND `position` = 0;
+	END IF;
+
+	INSERT INTO `posts_pivot_authors_data` (`authors_id`, `posts_id`, `position`)
+		SELECT NEW.`author_id`, NEW.`id`, 0 FROM DUAL WHERE NEW.`author_id` IS NOT NULL
+		ON DUPLICATE KEY UPDATE `position` = 0;
+END//
+DELIMITER ;
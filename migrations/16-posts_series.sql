@@ -0,0 +1,19 @@
+CREATE TABLE `posts_series` (
+	`id`          int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`title`       VARCHAR(191)     NOT NULL,
+	`slug`        VARCHAR(191)     NOT NULL,
+	`description` TEXT                 NULL,
+	`created_at`  DATETIME         NOT NULL,
+	`updated_at`  DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	UNIQUE KEY `posts_series_slug_unique` (`slug`)
+);
+
+CREATE TABLE `posts_pivot_series_data` (
+	`series_id` int(10) unsigned NOT NULL,
+	`posts_id`  int(10) unsigned NOT NULL,
+	`part`      int(10) unsigned NOT NULL,
+	PRIMARY KEY (`series_id`, `posts_id`),
+	UNIQUE KEY `posts_pivot_series_data_posts_id_unique` (`posts_id`),
+	UNIQUE KEY `posts_pivot_series_data_part_unique` (`series_id`, `part`)
+);
@@ -0,0 +1,21 @@
+CREATE TABLE `posts_pins` (
+	`id`           int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`posts_id`     int(10) unsigned NOT NULL,
+	`slot`         VARCHAR(50)      NOT NULL,
+	`position`     int(10) unsigned NOT NULL DEFAULT 0,
+	`pinned_until` DATETIME             NULL,
+	`created_at`   DATETIME         NOT NULL,
+	`updated_at`   DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	UNIQUE KEY `posts_pins_slot_posts_id_unique` (`slot`, `posts_id`),
+	KEY `posts_pins_posts_id_index` (`posts_id`)
+);
+
+INSERT INTO `posts_pins` (`posts_id`, `slot`, `position`, `created_at`, `updated_at`)
+	SELECT
+		`id`,
+		"homepage",
+		ROW_NUMBER() OVER (ORDER BY `date` DESC, `id` DESC) - 1,
+		NOW(),
+		NOW()
+	FROM `posts_data` WHERE `featured` = 1;
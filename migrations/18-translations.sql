@@ -0,0 +1,30 @@
+CREATE TABLE `posts_translations` (
+	`posts_id`    int(10) unsigned NOT NULL,
+	`locale`      VARCHAR(8)       NOT NULL,
+	`title`       VARCHAR(191)     NOT NULL,
+	`description` TEXT                 NULL,
+	`content`     LONGTEXT             NULL,
+	`created_at`  DATETIME         NOT NULL,
+	`updated_at`  DATETIME         NOT NULL,
+	PRIMARY KEY (`posts_id`, `locale`)
+);
+
+CREATE TABLE `pages_translations` (
+	`pages_id`   int(10) unsigned NOT NULL,
+	`locale`     VARCHAR(8)       NOT NULL,
+	`title`      VARCHAR(191)     NOT NULL,
+	`content`    TEXT             NOT NULL,
+	`created_at` DATETIME         NOT NULL,
+	`updated_at` DATETIME         NOT NULL,
+	PRIMARY KEY (`pages_id`, `locale`)
+);
+
+CREATE TABLE `menu_items_translations` (
+	`menu_items_id` int(10) unsigned NOT NULL,
+	`locale`        VARCHAR(8)       NOT NULL,
+	`name`          VARCHAR(191)     NOT NULL,
+	`link`          VARCHAR(191)         NULL,
+	`created_at`    DATETIME         NOT NULL,
+	`updated_at`    DATETIME         NOT NULL,
+	PRIMARY KEY (`menu_items_id`, `locale`)
+);
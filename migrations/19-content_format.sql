@@ -0,0 +1,5 @@
+ALTER TABLE `posts_data`
+	ADD COLUMN `content_format` ENUM('html', 'markdown') NOT NULL DEFAULT 'html' AFTER `content`;
+
+ALTER TABLE `pages`
+	ADD COLUMN `content_format` ENUM('html', 'markdown') NOT NULL DEFAULT 'html' AFTER `content`;
@@ -0,0 +1,21 @@
+ALTER TABLE `posts_data`
+	ADD COLUMN `index_image_meta` JSON NULL AFTER `index_image`;
+
+UPDATE `posts_data` SET `images` = JSON_ARRAY()
+	WHERE JSON_TYPE(`images`) NOT IN ('ARRAY', 'OBJECT');
+
+UPDATE `posts_data` SET `images` = COALESCE(JSON_EXTRACT(`images`, '$.*'), JSON_ARRAY())
+	WHERE JSON_TYPE(`images`) = 'OBJECT';
+
+UPDATE `posts_data` SET `images` = COALESCE(
+	(
+		SELECT JSON_ARRAYAGG(JSON_OBJECT('file', `image`.`file`))
+		FROM JSON_TABLE(
+			`posts_data`.`images`,
+			'$[*]' COLUMNS (`file` VARCHAR(191) PATH '$' NULL ON ERROR)
+		) AS `image`
+		WHERE `image`.`file` IS NOT NULL
+	),
+	JSON_ARRAY()
+)
+	WHERE JSON_TYPE(JSON_EXTRACT(`images`, '$[0]')) = 'STRING';
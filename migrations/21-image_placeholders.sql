@@ -0,0 +1,10 @@
+CREATE TABLE `image_placeholders` (
+	`directory`      VARCHAR(64)  NOT NULL,
+	`file`           VARCHAR(191) NOT NULL,
+	`modified_at`    DATETIME     NOT NULL,
+	`blurhash`       VARCHAR(64)  NOT NULL,
+	`dominant_color` CHAR(7)      NOT NULL,
+	`created_at`     DATETIME     NOT NULL,
+	`updated_at`     DATETIME     NOT NULL,
+	PRIMARY KEY (`directory`, `file`)
+);
@@ -0,0 +1,20 @@
+CREATE TABLE `albums` (
+	`id`          int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`title`       VARCHAR(191)     NOT NULL,
+	`description` TEXT                 NULL,
+	`date`        DATETIME         NOT NULL,
+	`cover`       VARCHAR(191)         NULL,
+	`cover_meta`  JSON                 NULL,
+	`images`      JSON             NOT NULL,
+	`published`   TINYINT(1)       NOT NULL DEFAULT 0,
+	`created_at`  DATETIME         NOT NULL,
+	`updated_at`  DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	KEY `albums_date_index` (`date`)
+);
+
+CREATE TABLE `albums_pivot_labels_data` (
+	`labels_id` int(10) unsigned NOT NULL,
+	`albums_id` int(10) unsigned NOT NULL,
+	PRIMARY KEY (`labels_id`, `albums_id`)
+);
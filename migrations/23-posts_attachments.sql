@@ -0,0 +1,14 @@
+CREATE TABLE `posts_attachments` (
+	`id`             int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`posts_id`       int(10) unsigned NOT NULL,
+	`file`           VARCHAR(191)     NOT NULL,
+	`name`           VARCHAR(191)     NOT NULL,
+	`size`           bigint unsigned  NOT NULL,
+	`mime_type`      VARCHAR(127)     NOT NULL,
+	`position`       int(10) unsigned NOT NULL DEFAULT 0,
+	`download_count` int(10) unsigned NOT NULL DEFAULT 0,
+	`created_at`     DATETIME         NOT NULL,
+	`updated_at`     DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	KEY `posts_attachments_posts_id_index` (`posts_id`)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE `posts_views` (
+	`posts_id` int(10) unsigned NOT NULL,
+	`date`     DATE             NOT NULL,
+	`views`    int(10) unsigned NOT NULL DEFAULT 0,
+	PRIMARY KEY (`posts_id`, `date`),
+	KEY `posts_views_date_index` (`date`)
+);
@@ -0,0 +1,15 @@
+ALTER TABLE `events_data`
+	ADD COLUMN `recurrence_rule` VARCHAR(255) NULL DEFAULT NULL AFTER `color`;
+
+CREATE TABLE `events_exceptions` (
+	`events_id`   int(10) unsigned NOT NULL,
+	`occurrence`  DATETIME         NOT NULL,
+	`cancelled`   tinyint(1)       NOT NULL DEFAULT 0,
+	`date_from`   DATETIME         NULL DEFAULT NULL,
+	`date_to`     DATETIME         NULL DEFAULT NULL,
+	`title`       VARCHAR(191)     NULL DEFAULT NULL,
+	`description` TEXT             NULL DEFAULT NULL,
+	`created_at`  DATETIME         NOT NULL,
+	`updated_at`  DATETIME         NOT NULL,
+	PRIMARY KEY (`events_id`, `occurrence`)
+);
@@ -0,0 +1,24 @@
+CREATE TABLE `events_categories` (
+	`id`         int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`name`       VARCHAR(191)     NOT NULL,
+	`color`      VARCHAR(7)       NOT NULL,
+	`icon`       VARCHAR(63)      NULL DEFAULT NULL,
+	`public`     tinyint(1)       NOT NULL DEFAULT 1,
+	`position`   int(10) unsigned NOT NULL DEFAULT 0,
+	`created_at` DATETIME         NOT NULL,
+	`updated_at` DATETIME         NOT NULL,
+	PRIMARY KEY (`id`)
+);
+
+ALTER TABLE `events_data`
+	ADD COLUMN `category_id` int(10) unsigned NULL DEFAULT NULL AFTER `color`,
+	ADD KEY `events_data_category_id_index` (`category_id`);
+
+-- Every colour used so far becomes a category, named after the colour until renamed.
+INSERT INTO `events_categories` (`name`, `color`, `created_at`, `updated_at`)
+	SELECT DISTINCT LOWER(`color`), LOWER(`color`), NOW(), NOW()
+	FROM `events_data`;
+
+UPDATE `events_data`
+	JOIN `events_categories` ON `events_categories`.`color` = LOWER(`events_data`.`color`)
+	SET `events_data`.`category_id` = `events_categories`.`id`;
@@ -0,0 +1,24 @@
+CREATE TABLE `school_years` (
+	`id`             int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`name`           VARCHAR(191)     NOT NULL,
+	`first_day`      DATE             NOT NULL,
+	`first_term_end` DATE             NOT NULL,
+	`last_day`       DATE             NOT NULL,
+	`created_at`     DATETIME         NOT NULL,
+	`updated_at`     DATETIME         NOT NULL,
+	PRIMARY KEY (`id`)
+);
+
+-- `kind` is one of `holiday`, `break`, `bridge_day` and `working_saturday`.
+CREATE TABLE `school_calendar_entries` (
+	`id`              int(10) unsigned NOT NULL AUTO_INCREMENT,
+	`school_years_id` int(10) unsigned NOT NULL,
+	`kind`            VARCHAR(31)      NOT NULL,
+	`name`            VARCHAR(191)     NULL DEFAULT NULL,
+	`first_day`       DATE             NOT NULL,
+	`last_day`        DATE             NOT NULL,
+	`created_at`      DATETIME         NOT NULL,
+	`updated_at`      DATETIME         NOT NULL,
+	PRIMARY KEY (`id`),
+	KEY `school_calendar_entries_school_years_id_index` (`school_years_id`)
+);
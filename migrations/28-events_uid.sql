@@ -0,0 +1,4 @@
+-- UID of events imported from iCalendar files, to recognise them when imported again.
+ALTER TABLE `events_data`
+	ADD COLUMN `uid` VARCHAR(255) NULL DEFAULT NULL AFTER `recurrence_rule`,
+	ADD KEY `events_data_uid_index` (`uid`);
@@ -0,0 +1,262 @@
+use super::{
+    IcsWriter, Series, TIMEZONE, find_exceptions, format_date, format_local, format_utc,
+    in_categories, local_to_utc, occurring, series, visible,
+};
+use crate::{
+    Config,
+    entity::{
+        events_categories::Entity as EventsCategories,
+        events_data::{self, Column, Entity as EventsData},
+    },
+};
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+use sea_orm::{DatabaseConnection, DbErr, QueryOrder, prelude::*};
+use serde::Deserialize;
+use std::collections::HashMap;
+use url::Url;
+
+/// Longest window before or after now the feed covers, in days.
+const MAX_WINDOW_DAYS: u32 = 3650;
+
+/// Query parameters of the calendar feed.
+#[derive(Deserialize, Debug, Default)]
+pub struct FeedOptions {
+    /// Comma-separated colours of the events to include, with or without the `#`.
+    pub color: Option<String>,
+    /// Comma-separated IDs of the categories of the events to include.
+    pub category: Option<String>,
+    /// Include events ending at most this many days ago.
+    pub past_days: Option<u32>,
+    /// Include events starting at most this many days from now.
+    pub future_days: Option<u32>,
+}
+
+impl FeedOptions {
+    fn colors(&self) -> Option<Vec<String>> {
+        let colors = self.color.as_ref()?;
+
+        Some(
+            colors
+                .split(',')
+                .map(str::trim)
+                .filter(|color| !color.is_empty())
+                .map(|color| format!("#{}", color.trim_start_matches('#')))
+                .collect(),
+        )
+    }
+
+    fn categories(&self) -> Option<Vec<u32>> {
+        let categories = self.category.as_ref()?;
+
+        Some(
+            categories
+                .split(',')
+                .filter_map(|category| category.trim().parse().ok())
+                .collect(),
+        )
+    }
+}
+
+/// Domain part of the event UIDs, the host of `PUBLIC_BASE_URL`.
+fn uid_domain(config: &Config) -> String {
+    Url::parse(&config.public_base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned))
+        .unwrap_or_else(|| "localhost".to_owned())
+}
+
+/// Whether an event lasts whole days, from midnight to midnight.
+fn is_all_day(from: NaiveDateTime, to: NaiveDateTime) -> bool {
+    from.time() == NaiveTime::MIN && to.time() == NaiveTime::MIN
+}
+
+/// Write a time property, as a date for all-day events.
+fn write_time(writer: &mut IcsWriter, name: &str, time: NaiveDateTime, all_day: bool) {
+    if all_day {
+        writer.property(&format!("{name};VALUE=DATE"), &format_date(time.date()));
+    } else {
+        writer.property(&format!("{name};TZID={TIMEZONE}"), &format_local(time));
+    }
+}
+
+fn write_dates(writer: &mut IcsWriter, from: NaiveDateTime, to: NaiveDateTime) {
+    if is_all_day(from, to) {
+        write_time(writer, "DTSTART", from, true);
+        write_time(writer, "DTEND", to.max(from + Duration::days(1)), true);
+    } else {
+        write_time(writer, "DTSTART", from, false);
+        write_time(writer, "DTEND", to, false);
+    }
+}
+
+fn write_text(
+    writer: &mut IcsWriter,
+    title: &str,
+    description: Option<&str>,
+    category: Option<&str>,
+) {
+    writer.text("SUMMARY", title);
+
+    if let Some(category) = category {
+        writer.text("CATEGORIES", category);
+    }
+
+    if let Some(description) = description
+        && !description.is_empty()
+    {
+        writer.text("DESCRIPTION", description);
+    }
+}
+
+/// Write an event, with its recurrence rule and the exceptions to it if it recurs.
+fn write_event(
+    writer: &mut IcsWriter,
+    domain: &str,
+    event: &events_data::Model,
+    category: Option<&str>,
+    series: Option<&Series>,
+) {
+    let uid = event
+        .uid
+        .clone()
+        .unwrap_or_else(|| format!("event-{}@{domain}", event.id));
+    let all_day = is_all_day(event.date_from, event.date_to);
+
+    writer.begin("VEVENT");
+    writer.property("UID", &uid);
+    writer.property("DTSTAMP", &format_utc(local_to_utc(event.updated_at)));
+    writer.property("CREATED", &format_utc(local_to_utc(event.created_at)));
+    writer.property("LAST-MODIFIED", &format_utc(local_to_utc(event.updated_at)));
+    write_dates(writer, event.date_from, event.date_to);
+
+    if let Some(series) = series {
+        writer.property("RRULE", &series.rule().to_ics(all_day));
+
+        for exception in series.exceptions() {
+            if exception.cancelled {
+                write_time(writer, "EXDATE", exception.occurrence, all_day);
+            }
+        }
+    }
+
+    write_text(writer, &event.title, event.description.as_deref(), category);
+    writer.end("VEVENT");
+
+    // Changed occurrences are events of their own, with the UID of the series.
+    for exception in series.iter().flat_map(|series| series.exceptions()) {
+        if exception.cancelled {
+            continue;
+        }
+
+        let from = exception.date_from.unwrap_or(exception.occurrence);
+        let to = exception
+            .date_to
+            .unwrap_or(exception.occurrence + (event.date_to - event.date_from));
+
+        writer.begin("VEVENT");
+        writer.property("UID", &uid);
+        writer.property("DTSTAMP", &format_utc(local_to_utc(exception.updated_at)));
+        writer.property(
+            "LAST-MODIFIED",
+            &format_utc(local_to_utc(exception.updated_at)),
+        );
+        write_time(writer, "RECURRENCE-ID", exception.occurrence, all_day);
+        write_dates(writer, from, to);
+        write_text(
+            writer,
+            exception.title.as_deref().unwrap_or(&event.title),
+            exception
+                .description
+                .as_deref()
+                .or(event.description.as_deref()),
+            category,
+        );
+        writer.end("VEVENT");
+    }
+}
+
+/// Render the public events around today as an iCalendar feed.
+///
+/// The window defaults to `CALENDAR_PAST_DAYS` before and `CALENDAR_FUTURE_DAYS` after now,
+/// and is at most [`MAX_WINDOW_DAYS`] each way.
+pub async fn events_feed(
+    db: &DatabaseConnection,
+    config: &Config,
+    options: &FeedOptions,
+) -> Result<String, DbErr> {
+    let now = Local::now().naive_local();
+    let past_days = options
+        .past_days
+        .unwrap_or(config.calendar_past_days)
+        .min(MAX_WINDOW_DAYS);
+    let future_days = options
+        .future_days
+        .unwrap_or(config.calendar_future_days)
+        .min(MAX_WINDOW_DAYS);
+    let start = now - Duration::days(past_days.into());
+    let end = now + Duration::days(future_days.into());
+
+    let mut query = EventsData::find()
+        .filter(occurring(start, Some(end)))
+        .filter(visible(false))
+        .filter(in_categories(options.categories().as_deref()));
+
+    if let Some(colors) = options.colors() {
+        query = query.filter(Column::Color.is_in(colors));
+    }
+
+    let events = query
+        .order_by_asc(Column::DateFrom)
+        .order_by_asc(Column::Id)
+        .all(db)
+        .await?;
+
+    let mut exceptions = find_exceptions(
+        db,
+        events
+            .iter()
+            .filter(|event| event.recurrence_rule.is_some())
+            .map(|event| event.id),
+    )
+    .await?;
+
+    let categories: HashMap<_, _> = EventsCategories::find()
+        .all(db)
+        .await?
+        .into_iter()
+        .map(|category| (category.id, category.name))
+        .collect();
+
+    let domain = uid_domain(config);
+    let mut writer = IcsWriter::new();
+
+    writer.begin("VCALENDAR");
+    writer.property("VERSION", "2.0");
+    writer.property("PRODID", "-//website-backend//Events//HU");
+    writer.property("CALSCALE", "GREGORIAN");
+    writer.property("METHOD", "PUBLISH");
+    writer.property("X-WR-TIMEZONE", TIMEZONE);
+    writer.timezone();
+
+    for event in &events {
+        let category = event
+            .category_id
+            .and_then(|id| categories.get(&id))
+            .map(String::as_str);
+
+        match series(event, &mut exceptions) {
+            Some(series) if series.instances(start, Some(end)).next().is_some() => {
+                write_event(&mut writer, &domain, event, category, Some(&series));
+            }
+            Some(_) => {}
+            None if event.date_to >= start => {
+                write_event(&mut writer, &domain, event, category, None)
+            }
+            None => {}
+        }
+    }
+
+    writer.end("VCALENDAR");
+
+    Ok(writer.finish())
+}
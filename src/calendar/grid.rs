@@ -0,0 +1,225 @@
+use chrono::{Datelike, Days, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime};
+
+/// The days shown in the calendar of a month: whole weeks from the Monday on or before
+/// its first day to the Sunday on or after its last day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonthGrid {
+    first: NaiveDate,
+    start: NaiveDate,
+    last: NaiveDate,
+    /// The day after the last day of the grid.
+    end: NaiveDate,
+}
+
+impl MonthGrid {
+    /// The grid of a month, or `None` if the month or a day of its grid does not exist.
+    pub fn new(year: i32, month: u32) -> Option<Self> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let month_last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+        let start =
+            first.checked_sub_days(Days::new(first.weekday().num_days_from_monday().into()))?;
+        let last = month_last.checked_add_days(Days::new(
+            (6 - month_last.weekday().num_days_from_monday()).into(),
+        ))?;
+
+        Some(Self {
+            first,
+            start,
+            last,
+            end: last.succ_opt()?,
+        })
+    }
+
+    /// Start of the first day of the grid.
+    pub fn start(&self) -> NaiveDateTime {
+        self.start.and_time(NaiveTime::MIN)
+    }
+
+    /// End of the last day of the grid, exclusive.
+    pub fn end(&self) -> NaiveDateTime {
+        self.end.and_time(NaiveTime::MIN)
+    }
+
+    /// The last day of the grid, a Sunday.
+    pub fn last(&self) -> NaiveDate {
+        self.last
+    }
+
+    /// The Mondays starting the weeks of the grid.
+    pub fn weeks(&self) -> impl Iterator<Item = NaiveDate> + use<> {
+        let start = self.start;
+        let weeks = (self.end - self.start).num_weeks();
+
+        (0..weeks).map(move |week| start + Duration::weeks(week))
+    }
+
+    /// Whether `date` is in the month, not in the adjacent weeks.
+    pub fn in_month(&self, date: NaiveDate) -> bool {
+        date.year() == self.first.year() && date.month() == self.first.month()
+    }
+}
+
+/// The days an event is shown on: from the day it starts to the day it ends, not counting
+/// an end at midnight.
+pub fn event_days(from: NaiveDateTime, to: NaiveDateTime) -> (NaiveDate, NaiveDate) {
+    let last = if to > from {
+        (to - Duration::seconds(1)).date()
+    } else {
+        from.date()
+    };
+
+    (from.date(), last.max(from.date()))
+}
+
+/// Where an event is shown on a day of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    /// Index of the event in the laid out list.
+    pub index: usize,
+    /// Row of the event within its week, the same on all its days in the week.
+    pub lane: usize,
+    /// Whether the event started on an earlier day.
+    pub continues_before: bool,
+    /// Whether the event goes on to a later day.
+    pub continues_after: bool,
+}
+
+/// Lay out events over the week starting on `monday`, given their first and last days.
+///
+/// Returns the placements on each day of the week, by lane. Events are put in the lowest
+/// free lane, starting with the earliest and longest ones, so multi-day events keep
+/// their row within the week.
+pub fn layout_week(monday: NaiveDate, days: &[(NaiveDate, NaiveDate)]) -> [Vec<Placement>; 7] {
+    let sunday = monday + Duration::days(6);
+
+    let mut segments: Vec<_> = days
+        .iter()
+        .enumerate()
+        .filter(|(_, (first, last))| *first <= sunday && *last >= monday)
+        .map(|(index, (first, last))| (index, (*first).max(monday), (*last).min(sunday)))
+        .collect();
+
+    segments.sort_by_key(|(index, first, last)| (*first, std::cmp::Reverse(*last), *index));
+
+    // Last day taken in each lane.
+    let mut lanes: Vec<NaiveDate> = Vec::new();
+    let mut week: [Vec<Placement>; 7] = Default::default();
+
+    for (index, first, last) in segments {
+        let lane = match lanes.iter().position(|taken| *taken < first) {
+            Some(lane) => {
+                lanes[lane] = last;
+                lane
+            }
+            None => {
+                lanes.push(last);
+                lanes.len() - 1
+            }
+        };
+
+        let (event_first, event_last) = days[index];
+
+        for date in first.iter_days().take_while(|date| *date <= last) {
+            week[(date - monday).num_days() as usize].push(Placement {
+                index,
+                lane,
+                continues_before: date > event_first,
+                continues_after: date < event_last,
+            });
+        }
+    }
+
+    for day in &mut week {
+        day.sort_by_key(|placement| placement.lane);
+    }
+
+    week
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn covers_whole_weeks() {
+        // February 2021 starts on a Monday and ends on a Sunday.
+        let grid = MonthGrid::new(2021, 2).unwrap();
+
+        assert_eq!(grid.start().date(), date("2021-02-01"));
+        assert_eq!(grid.last(), date("2021-02-28"));
+        assert_eq!(grid.end().date(), date("2021-03-01"));
+        assert_eq!(grid.weeks().count(), 4);
+
+        let grid = MonthGrid::new(2025, 3).unwrap();
+        let weeks: Vec<_> = grid.weeks().collect();
+
+        assert_eq!(grid.start().date(), date("2025-02-24"));
+        assert_eq!(grid.last(), date("2025-04-06"));
+        assert_eq!(weeks.len(), 6);
+        assert_eq!(weeks[5], date("2025-03-31"));
+        assert!(!grid.in_month(date("2025-02-28")));
+        assert!(grid.in_month(date("2025-03-31")));
+    }
+
+    #[test]
+    fn rejects_months_outside_dates() {
+        assert_eq!(MonthGrid::new(2025, 13), None);
+        assert_eq!(MonthGrid::new(NaiveDate::MIN.year(), 1), None);
+        assert_eq!(MonthGrid::new(NaiveDate::MAX.year(), 12), None);
+        assert_eq!(MonthGrid::new(i32::MAX, 1), None);
+    }
+
+    #[test]
+    fn ends_events_before_midnight() {
+        let time = |value: &str| date(value).and_time(NaiveTime::MIN);
+
+        assert_eq!(
+            event_days(time("2025-03-03"), time("2025-03-05")),
+            (date("2025-03-03"), date("2025-03-04"))
+        );
+        assert_eq!(
+            event_days(time("2025-03-03"), time("2025-03-03")),
+            (date("2025-03-03"), date("2025-03-03"))
+        );
+    }
+
+    #[test]
+    fn keeps_lanes_of_multi_day_events() {
+        let monday = date("2025-03-03");
+        let days = [
+            (date("2025-03-04"), date("2025-03-04")),
+            // Starts in the previous week.
+            (date("2025-02-27"), date("2025-03-05")),
+            (date("2025-03-05"), date("2025-03-11")),
+            (date("2025-03-06"), date("2025-03-06")),
+            // Outside the week.
+            (date("2025-03-10"), date("2025-03-10")),
+        ];
+
+        let lanes = |week: &[Vec<Placement>; 7], day: usize| -> Vec<(usize, usize)> {
+            week[day]
+                .iter()
+                .map(|placement| (placement.index, placement.lane))
+                .collect()
+        };
+
+        let week = layout_week(monday, &days);
+
+        assert_eq!(lanes(&week, 0), [(1, 0)]);
+        assert_eq!(lanes(&week, 1), [(1, 0), (0, 1)]);
+        assert_eq!(lanes(&week, 2), [(1, 0), (2, 1)]);
+        assert_eq!(lanes(&week, 3), [(3, 0), (2, 1)]);
+        assert_eq!(lanes(&week, 6), [(2, 1)]);
+
+        let first = week[0][0];
+
+        assert!(first.continues_before && first.continues_after);
+        assert!(week[2][0].continues_before && !week[2][0].continues_after);
+        assert!(!week[2][1].continues_before && week[6][0].continues_after);
+        assert!(week.iter().flatten().all(|placement| placement.index != 4));
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{Datelike, NaiveDate};
+
+/// Easter Sunday of a year, by the anonymous Gregorian algorithm.
+fn easter(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let n = h + l - 7 * m + 114;
+
+    NaiveDate::from_ymd_opt(year, (n / 31) as u32, (n % 31 + 1) as u32)
+}
+
+/// Name of the Hungarian public holiday on `date`, if it is one.
+///
+/// Schools are closed on these days.
+pub fn public_holiday(date: NaiveDate) -> Option<&'static str> {
+    let fixed = match (date.month(), date.day()) {
+        (1, 1) => Some("Újév"),
+        (3, 15) => Some("Nemzeti ünnep"),
+        (5, 1) => Some("A munka ünnepe"),
+        (8, 20) => Some("Az államalapítás ünnepe"),
+        (10, 23) => Some("Nemzeti ünnep"),
+        (11, 1) => Some("Mindenszentek"),
+        (12, 24) => Some("Szenteste"),
+        (12, 25) => Some("Karácsony"),
+        (12, 26) => Some("Karácsony másnapja"),
+        _ => None,
+    };
+
+    if fixed.is_some() {
+        return fixed;
+    }
+
+    match (date - easter(date.year())?).num_days() {
+        -2 => Some("Nagypéntek"),
+        0 => Some("Húsvét"),
+        1 => Some("Húsvéthétfő"),
+        49 => Some("Pünkösd"),
+        50 => Some("Pünkösdhétfő"),
+        _ => None,
+    }
+}
+
+/// Whether `date` is a Saturday or Sunday.
+pub fn is_weekend(date: NaiveDate) -> bool {
+    date.weekday().num_days_from_monday() >= 5
+}
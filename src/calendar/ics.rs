@@ -0,0 +1,455 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+
+/// Time zone of the times stored in the database.
+pub const TIMEZONE: &str = "Europe/Budapest";
+
+/// Longest line allowed by RFC 5545, in octets, without the line break.
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Builds an iCalendar document, folding long lines as RFC 5545 requires.
+#[derive(Default)]
+pub struct IcsWriter {
+    output: String,
+}
+
+impl IcsWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&mut self, component: &str) {
+        self.property("BEGIN", component);
+    }
+
+    pub fn end(&mut self, component: &str) {
+        self.property("END", component);
+    }
+
+    /// Write a property whose value is already in iCalendar format.
+    ///
+    /// `name` may contain parameters, e.g. `DTSTART;VALUE=DATE`.
+    pub fn property(&mut self, name: &str, value: &str) {
+        let line = format!("{name}:{value}");
+        let mut length = 0;
+
+        for c in line.chars() {
+            if length + c.len_utf8() > MAX_LINE_LENGTH {
+                self.output.push_str("\r\n ");
+                length = 1;
+            }
+
+            self.output.push(c);
+            length += c.len_utf8();
+        }
+
+        self.output.push_str("\r\n");
+    }
+
+    /// Write a text property, escaping its value.
+    pub fn text(&mut self, name: &str, value: &str) {
+        self.property(name, &escape_text(value));
+    }
+
+    /// Write the definition of [`TIMEZONE`], referenced by `TZID` parameters.
+    pub fn timezone(&mut self) {
+        self.begin("VTIMEZONE");
+        self.property("TZID", TIMEZONE);
+        self.property("X-LIC-LOCATION", TIMEZONE);
+
+        self.begin("DAYLIGHT");
+        self.property("TZOFFSETFROM", "+0100");
+        self.property("TZOFFSETTO", "+0200");
+        self.property("TZNAME", "CEST");
+        self.property("DTSTART", "19700329T020000");
+        self.property("RRULE", "FREQ=YEARLY;BYMONTH=3;BYDAY=-1SU");
+        self.end("DAYLIGHT");
+
+        self.begin("STANDARD");
+        self.property("TZOFFSETFROM", "+0200");
+        self.property("TZOFFSETTO", "+0100");
+        self.property("TZNAME", "CET");
+        self.property("DTSTART", "19701025T030000");
+        self.property("RRULE", "FREQ=YEARLY;BYMONTH=10;BYDAY=-1SU");
+        self.end("STANDARD");
+
+        self.end("VTIMEZONE");
+    }
+
+    pub fn finish(self) -> String {
+        self.output
+    }
+}
+
+/// A content line of an iCalendar document, with its value still escaped.
+#[derive(Debug, Clone)]
+pub struct Property {
+    /// Name of the property, in upper case.
+    pub name: String,
+    /// Parameters of the property, with names in upper case and quotes removed.
+    pub params: Vec<(String, String)>,
+    pub value: String,
+}
+
+impl Property {
+    /// Parse a content line, e.g. `DTSTART;TZID=Europe/Budapest:20250101T100000`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = Vec::new();
+        let mut part = String::new();
+        let mut quoted = false;
+        let mut value = None;
+
+        for (index, c) in line.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ';' if !quoted => parts.push(std::mem::take(&mut part)),
+                ':' if !quoted => {
+                    parts.push(std::mem::take(&mut part));
+                    value = Some(&line[index + 1..]);
+                    break;
+                }
+                c => part.push(c),
+            }
+        }
+
+        let mut parts = parts.into_iter();
+        let name = parts.next()?.to_ascii_uppercase();
+        let params = parts
+            .filter_map(|param| {
+                let (name, value) = param.split_once('=')?;
+
+                Some((name.to_ascii_uppercase(), value.to_owned()))
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: value?.to_owned(),
+        })
+    }
+
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param, _)| param == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The value of a text property, unescaped.
+    pub fn text(&self) -> String {
+        unescape_text(&self.value)
+    }
+}
+
+/// Unfold the lines of an iCalendar document and parse them into properties.
+///
+/// Lines that are not valid content lines are skipped.
+pub fn read_properties(input: &str) -> Vec<Property> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in input.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(continued) if !lines.is_empty() => lines.last_mut().unwrap().push_str(continued),
+            _ => lines.push(line.to_owned()),
+        }
+    }
+
+    lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| Property::parse(line))
+        .collect()
+}
+
+/// Escape a text value: backslashes, separators and line breaks.
+pub fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Reverse [`escape_text`].
+pub fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+
+    unescaped
+}
+
+pub fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Format a time without a time zone, to be used with a `TZID` parameter.
+pub fn format_local(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%S").to_string()
+}
+
+pub fn format_utc(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn last_sunday(year: i32, month: u32) -> NaiveDate {
+    let last = if month < 12 {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    }
+    .and_then(|next| next.pred_opt())
+    .unwrap();
+
+    last - Duration::days(last.weekday().num_days_from_sunday().into())
+}
+
+/// Convert a time in [`TIMEZONE`] to UTC, using the EU summer time rules also given in
+/// its `VTIMEZONE`.
+pub fn local_to_utc(time: NaiveDateTime) -> DateTime<Utc> {
+    let summer_start = last_sunday(time.year(), 3).and_hms_opt(2, 0, 0).unwrap();
+    let summer_end = last_sunday(time.year(), 10).and_hms_opt(3, 0, 0).unwrap();
+    let offset = if (summer_start..summer_end).contains(&time) {
+        2
+    } else {
+        1
+    };
+
+    (time - Duration::hours(offset)).and_utc()
+}
+
+/// Convert a UTC time to [`TIMEZONE`].
+pub fn utc_to_local(time: DateTime<Utc>) -> NaiveDateTime {
+    let time = time.naive_utc();
+    let summer_start = last_sunday(time.year(), 3).and_hms_opt(1, 0, 0).unwrap();
+    let summer_end = last_sunday(time.year(), 10).and_hms_opt(1, 0, 0).unwrap();
+    let offset = if (summer_start..summer_end).contains(&time) {
+        2
+    } else {
+        1
+    };
+
+    time + Duration::hours(offset)
+}
+
+/// Parse a `DURATION` value, e.g. `P1D` or `PT1H30M`.
+pub fn parse_duration(value: &str) -> Option<Duration> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    let mut time = false;
+
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' if number.is_empty() => time = true,
+            _ => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+
+                duration += match (c, time) {
+                    ('W', false) => Duration::weeks(amount),
+                    ('D', false) => Duration::days(amount),
+                    ('H', true) => Duration::hours(amount),
+                    ('M', true) => Duration::minutes(amount),
+                    ('S', true) => Duration::seconds(amount),
+                    _ => return None,
+                };
+            }
+        }
+    }
+
+    if !number.is_empty() {
+        return None;
+    }
+
+    Some(if negative { -duration } else { duration })
+}
+
+/// Parse a `DATE` or `DATE-TIME` value as a time in [`TIMEZONE`].
+///
+/// UTC times (ending in `Z`) are converted, other times are taken as local. Dates are
+/// returned as midnight, together with `true`.
+pub fn parse_time(value: &str) -> Option<(NaiveDateTime, bool)> {
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+
+        Some((utc_to_local(time.and_utc()), false))
+    } else if value.contains('T') {
+        Some((
+            NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+            false,
+        ))
+    } else {
+        Some((
+            NaiveDate::parse_from_str(value, "%Y%m%d")
+                .ok()?
+                .and_time(NaiveTime::MIN),
+            true,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn utc(value: &str) -> DateTime<Utc> {
+        time(value).and_utc()
+    }
+
+    #[test]
+    fn parses_times() {
+        assert_eq!(
+            parse_time("20250115T100000"),
+            Some((time("2025-01-15 10:00"), false))
+        );
+        assert_eq!(
+            parse_time("20250115T100000Z"),
+            Some((time("2025-01-15 11:00"), false))
+        );
+        assert_eq!(
+            parse_time("20250715T100000Z"),
+            Some((time("2025-07-15 12:00"), false))
+        );
+        assert_eq!(
+            parse_time("20250115"),
+            Some((time("2025-01-15 00:00"), true))
+        );
+        assert_eq!(parse_time("2025-01-15"), None);
+        assert_eq!(parse_time("20250115T25"), None);
+    }
+
+    #[test]
+    fn converts_around_summer_time() {
+        // Summer time starts at 2:00 on 30 March 2025 and ends at 3:00 on 26 October 2025.
+        assert_eq!(
+            local_to_utc(time("2025-03-30 01:59")),
+            utc("2025-03-30 00:59")
+        );
+        assert_eq!(
+            local_to_utc(time("2025-03-30 03:00")),
+            utc("2025-03-30 01:00")
+        );
+        assert_eq!(
+            local_to_utc(time("2025-10-26 02:30")),
+            utc("2025-10-26 00:30")
+        );
+        assert_eq!(
+            local_to_utc(time("2025-10-26 03:00")),
+            utc("2025-10-26 02:00")
+        );
+
+        assert_eq!(
+            utc_to_local(utc("2025-03-30 00:59")),
+            time("2025-03-30 01:59")
+        );
+        assert_eq!(
+            utc_to_local(utc("2025-03-30 01:00")),
+            time("2025-03-30 03:00")
+        );
+        assert_eq!(
+            utc_to_local(utc("2025-10-26 00:59")),
+            time("2025-10-26 02:59")
+        );
+        assert_eq!(
+            utc_to_local(utc("2025-10-26 01:00")),
+            time("2025-10-26 02:00")
+        );
+    }
+
+    #[test]
+    fn converts_back_and_forth() {
+        for value in ["2025-01-01 00:00", "2025-06-30 23:30", "2025-12-31 23:59"] {
+            assert_eq!(utc_to_local(local_to_utc(time(value))), time(value));
+        }
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("P1D"), Some(Duration::days(1)));
+        assert_eq!(parse_duration("PT1H30M"), Some(Duration::minutes(90)));
+        assert_eq!(
+            parse_duration("-P1W2DT3S"),
+            Some(-(Duration::days(9) + Duration::seconds(3)))
+        );
+        assert_eq!(parse_duration("P1H"), None);
+        assert_eq!(parse_duration("PT5"), None);
+        assert_eq!(parse_duration("1D"), None);
+    }
+
+    #[test]
+    fn escapes_text() {
+        let text = "Tea; biscuits, cake\\\nand more";
+
+        assert_eq!(escape_text(text), r"Tea\; biscuits\, cake\\\nand more");
+        assert_eq!(unescape_text(&escape_text(text)), text);
+        assert_eq!(unescape_text("Line\\NBreak\\"), "Line\nBreak");
+    }
+
+    #[test]
+    fn folds_and_unfolds_lines() {
+        let mut writer = IcsWriter::new();
+        let summary = "Évnyitó ünnepség a tornateremben, utána fogadás a könyvtárban";
+
+        writer.text("SUMMARY;LANGUAGE=hu", summary);
+
+        let output = writer.finish();
+
+        assert!(
+            output
+                .split("\r\n")
+                .all(|line| line.len() <= MAX_LINE_LENGTH)
+        );
+        assert!(output.contains("\r\n "));
+
+        let properties = read_properties(&output);
+
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].name, "SUMMARY");
+        assert_eq!(properties[0].param("LANGUAGE"), Some("hu"));
+        assert_eq!(properties[0].text(), summary);
+    }
+
+    #[test]
+    fn reads_quoted_parameters() {
+        let properties = read_properties(
+            "dtstart;tzid=\"Europe/Budapest\";x-note=\"a:b;c\":20250101T100000\r\nnot a property\r\n",
+        );
+
+        assert_eq!(properties.len(), 1);
+        assert_eq!(properties[0].name, "DTSTART");
+        assert_eq!(properties[0].param("TZID"), Some("Europe/Budapest"));
+        assert_eq!(properties[0].param("X-NOTE"), Some("a:b;c"));
+        assert_eq!(properties[0].value, "20250101T100000");
+    }
+}
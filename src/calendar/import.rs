@@ -0,0 +1,634 @@
+use super::{
+    Property, RecurrenceRule, TIMEZONE, find_exceptions, parse_duration, parse_time,
+    read_properties, unescape_text,
+};
+use crate::entity::{
+    events_categories::{self, Entity as EventsCategories},
+    events_data::{self, Column, Entity as EventsData},
+    events_exceptions::{self, Entity as EventsExceptions},
+};
+use chrono::{Duration, Local, NaiveDateTime, NaiveTime};
+use sea_orm::{
+    ActiveModelTrait, Condition, DatabaseConnection, DbErr, IntoActiveModel, Set, TransactionTrait,
+    prelude::*,
+};
+use std::{collections::HashSet, fmt::Display, path::PathBuf};
+
+/// Colour of events without any other, as `events_data.color` is required.
+const FALLBACK_COLOR: &str = "#000000";
+
+const USAGE: &str =
+    "Usage: import-events [--dry-run] [--category <name or ID>] [--color <colour>] <file.ics>";
+
+#[derive(thiserror::Error, Debug)]
+pub enum ImportError {
+    #[error("{}", USAGE)]
+    Usage,
+    #[error("Could not read {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("Unknown event category: {0}")]
+    UnknownCategory(String),
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+/// Arguments of the `import-events` command.
+#[derive(Debug, Default)]
+pub struct ImportOptions {
+    pub path: PathBuf,
+    /// Print the changes without writing them.
+    pub dry_run: bool,
+    /// Category of the events whose `CATEGORIES` name none, by name or ID.
+    pub category: Option<String>,
+    /// Colour of the events without a category or colour of their own.
+    pub color: Option<String>,
+}
+
+impl ImportOptions {
+    /// Parse the arguments following the command name.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, ImportError> {
+        let mut args = args.into_iter();
+        let mut options = Self::default();
+        let mut path = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--dry-run" => options.dry_run = true,
+                "--category" => options.category = Some(args.next().ok_or(ImportError::Usage)?),
+                "--color" => {
+                    options.color = Some(
+                        args.next()
+                            .as_deref()
+                            .and_then(parse_color)
+                            .ok_or(ImportError::Usage)?,
+                    )
+                }
+                _ if arg.starts_with("--") || path.is_some() => return Err(ImportError::Usage),
+                _ => path = Some(PathBuf::from(arg)),
+            }
+        }
+
+        options.path = path.ok_or(ImportError::Usage)?;
+
+        Ok(options)
+    }
+}
+
+/// A hex colour as `#rrggbb`, with or without the `#`.
+fn parse_color(color: &str) -> Option<String> {
+    let hex = color.trim().trim_start_matches('#');
+
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("#{}", hex.to_ascii_lowercase()))
+}
+
+/// An occurrence of a recurring event changed or cancelled in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Change {
+    occurrence: NaiveDateTime,
+    cancelled: bool,
+    date_from: Option<NaiveDateTime>,
+    date_to: Option<NaiveDateTime>,
+    title: Option<String>,
+    description: Option<String>,
+}
+
+impl From<events_exceptions::Model> for Change {
+    fn from(exception: events_exceptions::Model) -> Self {
+        Self {
+            occurrence: exception.occurrence,
+            cancelled: exception.cancelled,
+            date_from: exception.date_from,
+            date_to: exception.date_to,
+            title: exception.title,
+            description: exception.description,
+        }
+    }
+}
+
+/// An event read from the file.
+#[derive(Debug)]
+struct Imported {
+    uid: Option<String>,
+    date_from: NaiveDateTime,
+    date_to: NaiveDateTime,
+    all_day: bool,
+    title: String,
+    description: Option<String>,
+    /// Names in its `CATEGORIES` properties.
+    categories: Vec<String>,
+    color: Option<String>,
+    category_id: Option<i32>,
+    recurrence_rule: Option<String>,
+    changes: Vec<Change>,
+}
+
+enum Component {
+    Event(Imported),
+    /// An occurrence of the recurring event with the UID, given by its `RECURRENCE-ID`.
+    Change(String, Change),
+}
+
+/// The properties of the `VEVENT`s of a document, leaving out components nested in them,
+/// such as alarms.
+fn event_components(properties: Vec<Property>) -> Vec<Vec<Property>> {
+    let mut events = Vec::new();
+    let mut current: Option<Vec<Property>> = None;
+    let mut depth = 0;
+
+    for property in properties {
+        match property.name.as_str() {
+            "BEGIN" if current.is_some() => depth += 1,
+            "BEGIN" if property.value.eq_ignore_ascii_case("VEVENT") => current = Some(Vec::new()),
+            "END" if depth > 0 => depth -= 1,
+            "END" => events.extend(current.take()),
+            _ if depth == 0 => {
+                if let Some(current) = current.as_mut() {
+                    current.push(property);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    events
+}
+
+/// The time of a date or time property in [`TIMEZONE`], and whether it is a date.
+///
+/// Times in other time zones are taken as local times.
+fn property_time(property: &Property) -> Option<(NaiveDateTime, bool)> {
+    if let Some(tzid) = property.param("TZID")
+        && tzid != TIMEZONE
+    {
+        tracing::warn!(
+            "Importing {} in {tzid} as a {TIMEZONE} time",
+            property.value
+        );
+    }
+
+    parse_time(&property.value)
+}
+
+fn read_component(properties: &[Property]) -> Option<Component> {
+    let get = |name: &str| properties.iter().find(|property| property.name == name);
+
+    let uid = get("UID")
+        .map(|property| property.value.trim().to_owned())
+        .filter(|uid| !uid.is_empty());
+    let title = get("SUMMARY")
+        .map(|property| property.text().trim().to_owned())
+        .unwrap_or_default();
+
+    let Some((from, all_day)) = get("DTSTART").and_then(property_time) else {
+        tracing::warn!("Skipping event without a valid DTSTART: {title}");
+        return None;
+    };
+
+    let to = match get("DTEND").and_then(property_time) {
+        Some((to, _)) => to,
+        None => match get("DURATION").and_then(|property| parse_duration(&property.value)) {
+            Some(duration) => from + duration,
+            None if all_day => from + Duration::days(1),
+            None => from,
+        },
+    }
+    .max(from);
+
+    let description = get("DESCRIPTION")
+        .map(Property::text)
+        .filter(|description| !description.trim().is_empty());
+    let cancelled =
+        get("STATUS").is_some_and(|status| status.value.eq_ignore_ascii_case("CANCELLED"));
+
+    if let Some(recurrence_id) = get("RECURRENCE-ID") {
+        let (Some(uid), Some((occurrence, _))) = (uid, property_time(recurrence_id)) else {
+            tracing::warn!("Skipping changed occurrence without a valid UID or RECURRENCE-ID");
+            return None;
+        };
+
+        return Some(Component::Change(
+            uid,
+            Change {
+                occurrence,
+                cancelled,
+                date_from: (!cancelled).then_some(from),
+                date_to: (!cancelled).then_some(to),
+                title: (!cancelled).then_some(title),
+                description: description.filter(|_| !cancelled),
+            },
+        ));
+    }
+
+    if cancelled {
+        tracing::info!("Skipping cancelled event: {title}");
+        return None;
+    }
+
+    if title.is_empty() {
+        tracing::warn!("Skipping event without a SUMMARY on {from}");
+        return None;
+    }
+
+    let recurrence_rule =
+        get("RRULE").and_then(|property| match property.value.parse::<RecurrenceRule>() {
+            Ok(rule) => Some(rule.to_ics(all_day)),
+            Err(err) => {
+                tracing::warn!("Importing only the first occurrence of {title}: {err}");
+                None
+            }
+        });
+
+    // Occurrences left out of the series are cancelled.
+    let changes = properties
+        .iter()
+        .filter(|property| recurrence_rule.is_some() && property.name == "EXDATE")
+        .flat_map(|property| property.value.split(','))
+        .filter_map(parse_time)
+        .map(|(occurrence, _)| Change {
+            occurrence,
+            cancelled: true,
+            date_from: None,
+            date_to: None,
+            title: None,
+            description: None,
+        })
+        .collect();
+
+    Some(Component::Event(Imported {
+        uid,
+        date_from: from,
+        date_to: to,
+        all_day,
+        title,
+        description,
+        categories: properties
+            .iter()
+            .filter(|property| property.name == "CATEGORIES")
+            .flat_map(|property| property.value.split(','))
+            .map(|category| unescape_text(category).trim().to_owned())
+            .filter(|category| !category.is_empty())
+            .collect(),
+        color: get("COLOR").and_then(|property| parse_color(&property.value)),
+        category_id: None,
+        recurrence_rule,
+        changes,
+    }))
+}
+
+/// The events of an iCalendar document, with changed occurrences attached to their series.
+fn read_events(input: &str) -> Vec<Imported> {
+    let mut events: Vec<Imported> = Vec::new();
+    let mut changes = Vec::new();
+
+    for properties in event_components(read_properties(input)) {
+        match read_component(&properties) {
+            Some(Component::Event(event)) => {
+                // A later copy of an event replaces the earlier one.
+                match events
+                    .iter()
+                    .position(|other| event.uid.is_some() && other.uid == event.uid)
+                {
+                    Some(index) => events[index] = event,
+                    None => events.push(event),
+                }
+            }
+            Some(Component::Change(uid, change)) => changes.push((uid, change)),
+            None => {}
+        }
+    }
+
+    for (uid, mut change) in changes {
+        let Some(event) = events
+            .iter_mut()
+            .find(|event| event.uid.as_deref() == Some(&uid) && event.recurrence_rule.is_some())
+        else {
+            tracing::warn!("Skipping changed occurrence of unknown recurring event {uid}");
+            continue;
+        };
+
+        // Only what differs from the series is kept.
+        if change.date_from == Some(change.occurrence)
+            && change.date_to == Some(change.occurrence + (event.date_to - event.date_from))
+        {
+            change.date_from = None;
+            change.date_to = None;
+        }
+
+        if change.title.as_ref() == Some(&event.title) {
+            change.title = None;
+        }
+
+        if change.description == event.description {
+            change.description = None;
+        }
+
+        event
+            .changes
+            .retain(|other| other.occurrence != change.occurrence);
+        event.changes.push(change);
+    }
+
+    for event in &mut events {
+        event.changes.retain(|change| {
+            change.cancelled
+                || change.date_from.is_some()
+                || change.title.is_some()
+                || change.description.is_some()
+        });
+        event.changes.sort_by_key(|change| change.occurrence);
+    }
+
+    events
+}
+
+/// Set the category and colour of the imported events.
+///
+/// An event is put in the first category named in its `CATEGORIES`, or else in the default
+/// category. It takes the colour of its category, or else its own `COLOR`, or else the
+/// default colour, or else [`FALLBACK_COLOR`].
+fn classify(
+    events: &mut [Imported],
+    categories: &[events_categories::Model],
+    default_category: Option<&events_categories::Model>,
+    default_color: Option<&str>,
+) {
+    for event in events {
+        let category = event
+            .categories
+            .iter()
+            .find_map(|name| {
+                categories
+                    .iter()
+                    .find(|category| category.name.to_lowercase() == name.to_lowercase())
+            })
+            .or(default_category);
+
+        event.category_id = category.map(|category| category.id);
+        event.color = Some(
+            category
+                .map(|category| category.color.clone())
+                .or_else(|| event.color.take())
+                .or_else(|| default_color.map(str::to_owned))
+                .unwrap_or_else(|| FALLBACK_COLOR.to_owned()),
+        );
+    }
+}
+
+async fn find_category(
+    db: &DatabaseConnection,
+    category: &str,
+) -> Result<events_categories::Model, ImportError> {
+    let condition = match category.parse::<i32>() {
+        Ok(id) => events_categories::Column::Id.eq(id),
+        Err(_) => events_categories::Column::Name.eq(category),
+    };
+
+    EventsCategories::find()
+        .filter(condition)
+        .one(db)
+        .await?
+        .ok_or_else(|| ImportError::UnknownCategory(category.to_owned()))
+}
+
+/// The event already in the database that an imported one updates: the one with the same
+/// UID, or else one without a UID with the same title on the same day.
+fn find_existing<'a>(
+    event: &Imported,
+    existing: &'a [events_data::Model],
+    matched: &HashSet<i32>,
+) -> Option<&'a events_data::Model> {
+    existing
+        .iter()
+        .find(|other| event.uid.is_some() && other.uid == event.uid)
+        .or_else(|| {
+            existing.iter().find(|other| {
+                !matched.contains(&other.id)
+                    && other.uid.is_none()
+                    && other.title == event.title
+                    && other.date_from.date() == event.date_from.date()
+            })
+        })
+        .filter(|other| !matched.contains(&other.id))
+}
+
+fn show(value: Option<impl Display>) -> String {
+    value.map_or_else(|| "-".to_owned(), |value| value.to_string())
+}
+
+fn show_time(time: NaiveDateTime, all_day: bool) -> String {
+    if all_day && time.time() == NaiveTime::MIN {
+        time.format("%Y-%m-%d").to_string()
+    } else {
+        time.format("%Y-%m-%d %H:%M").to_string()
+    }
+}
+
+/// Fields of an existing event changed by the import, as `name: old -> new`.
+fn differences(
+    existing: &events_data::Model,
+    existing_changes: &[Change],
+    event: &Imported,
+) -> Vec<String> {
+    let mut differences = Vec::new();
+    let mut compare = |name: &str, old: String, new: String| {
+        if old != new {
+            differences.push(format!("{name}: {old} -> {new}"));
+        }
+    };
+
+    compare(
+        "from",
+        show_time(existing.date_from, event.all_day),
+        show_time(event.date_from, event.all_day),
+    );
+    compare(
+        "to",
+        show_time(existing.date_to, event.all_day),
+        show_time(event.date_to, event.all_day),
+    );
+    compare("title", existing.title.clone(), event.title.clone());
+    compare(
+        "description",
+        show(existing.description.as_ref()),
+        show(event.description.as_ref()),
+    );
+    compare(
+        "category",
+        show(existing.category_id),
+        show(event.category_id),
+    );
+    compare(
+        "color",
+        show(existing.color.as_ref()),
+        show(event.color.as_ref()),
+    );
+    compare(
+        "recurrence",
+        show(existing.recurrence_rule.as_ref()),
+        show(event.recurrence_rule.as_ref()),
+    );
+
+    if existing.uid.is_none() {
+        compare("uid", show(existing.uid.as_ref()), show(event.uid.as_ref()));
+    }
+
+    if existing_changes != event.changes {
+        compare(
+            "exceptions",
+            existing_changes.len().to_string(),
+            event.changes.len().to_string(),
+        );
+    }
+
+    differences
+}
+
+/// Import the events of an iCalendar file into `events_data`, printing the changes.
+///
+/// Events already imported, or entered by hand with the same title on the same day, are
+/// updated instead of added again. With `dry_run`, nothing is written.
+pub async fn import_events(
+    db: &DatabaseConnection,
+    options: &ImportOptions,
+) -> Result<(), ImportError> {
+    let input = std::fs::read_to_string(&options.path)
+        .map_err(|err| ImportError::Read(options.path.clone(), err))?;
+    let mut events = read_events(&input);
+
+    let default_category = match &options.category {
+        Some(category) => Some(find_category(db, category).await?),
+        None => None,
+    };
+    let categories = EventsCategories::find().all(db).await?;
+
+    classify(
+        &mut events,
+        &categories,
+        default_category.as_ref(),
+        options.color.as_deref(),
+    );
+
+    let existing = if events.is_empty() {
+        Vec::new()
+    } else {
+        EventsData::find()
+            .filter(
+                Condition::any()
+                    .add(Column::Uid.is_in(events.iter().filter_map(|event| event.uid.clone())))
+                    .add(Column::Title.is_in(events.iter().map(|event| event.title.clone()))),
+            )
+            .all(db)
+            .await?
+    };
+    let mut existing_changes = find_exceptions(db, existing.iter().map(|event| event.id)).await?;
+
+    let mut matched = HashSet::new();
+    let mut plan = Vec::new();
+    let (mut added, mut updated, mut unchanged) = (0, 0, 0);
+
+    for event in events {
+        let when = show_time(event.date_from, event.all_day);
+
+        let Some(found) = find_existing(&event, &existing, &matched) else {
+            println!("+ {when} {}", event.title);
+            added += 1;
+            let replace_changes = !event.changes.is_empty();
+            plan.push((event, None, replace_changes));
+            continue;
+        };
+
+        matched.insert(found.id);
+
+        let old_changes: Vec<Change> = existing_changes
+            .remove(&found.id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(Change::from)
+            .collect();
+        let differences = differences(found, &old_changes, &event);
+
+        if differences.is_empty() {
+            unchanged += 1;
+            continue;
+        }
+
+        println!("~ {when} {} (#{})", event.title, found.id);
+
+        for difference in &differences {
+            println!("    {difference}");
+        }
+
+        updated += 1;
+        let replace_changes = old_changes != event.changes;
+        plan.push((event, Some(found.clone()), replace_changes));
+    }
+
+    println!("{added} new, {updated} changed, {unchanged} unchanged");
+
+    if options.dry_run {
+        println!("Dry run, nothing was written");
+        return Ok(());
+    }
+
+    let txn = db.begin().await?;
+    let now = Local::now().naive_local();
+
+    for (event, existing, replace_changes) in plan {
+        let mut model = match existing {
+            Some(existing) => existing.into_active_model(),
+            None => events_data::ActiveModel {
+                created_at: Set(now),
+                ..Default::default()
+            },
+        };
+
+        model.date_from = Set(event.date_from);
+        model.date_to = Set(event.date_to);
+        model.title = Set(event.title);
+        model.description = Set(event.description);
+        model.color = Set(event.color);
+        model.category_id = Set(event.category_id);
+        model.recurrence_rule = Set(event.recurrence_rule);
+        model.updated_at = Set(now);
+
+        if event.uid.is_some() {
+            model.uid = Set(event.uid);
+        }
+
+        let id = model.save(&txn).await?.id.unwrap();
+
+        if !replace_changes {
+            continue;
+        }
+
+        EventsExceptions::delete_many()
+            .filter(events_exceptions::Column::EventsId.eq(id))
+            .exec(&txn)
+            .await?;
+
+        if !event.changes.is_empty() {
+            EventsExceptions::insert_many(event.changes.into_iter().map(|change| {
+                events_exceptions::ActiveModel {
+                    events_id: Set(id),
+                    occurrence: Set(change.occurrence),
+                    cancelled: Set(change.cancelled),
+                    date_from: Set(change.date_from),
+                    date_to: Set(change.date_to),
+                    title: Set(change.title),
+                    description: Set(change.description),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                }
+            }))
+            .exec(&txn)
+            .await?;
+        }
+    }
+
+    txn.commit().await?;
+
+    tracing::info!("Imported {} events", added + updated);
+
+    Ok(())
+}
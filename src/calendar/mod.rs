@@ -0,0 +1,180 @@
+mod feed;
+mod grid;
+mod holidays;
+mod ics;
+mod import;
+mod recurrence;
+mod school;
+
+pub use feed::*;
+pub use grid::*;
+pub use holidays::*;
+pub use ics::*;
+pub use import::*;
+pub use recurrence::*;
+pub use school::*;
+
+use crate::entity::{
+    events_categories::{self, Entity as EventsCategories},
+    events_data::{self, Column, Entity as EventsData},
+    events_exceptions::{self, Entity as EventsExceptions},
+};
+use chrono::NaiveDateTime;
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DbErr, QueryOrder, QuerySelect, prelude::*,
+    sea_query::Query,
+};
+use std::collections::HashMap;
+
+/// Most occurrences listed for a single recurring event in one query.
+pub const MAX_OCCURRENCES: usize = 1000;
+
+/// Events overlapping the range from `start` to `end`, or ending after `start` if the
+/// range is open.
+///
+/// Events that started before `start` but have not ended yet are included.
+pub fn overlapping(start: NaiveDateTime, end: Option<NaiveDateTime>) -> Condition {
+    Condition::all()
+        .add(Column::DateTo.gte(start))
+        .add_option(end.map(|end| Column::DateFrom.lt(end)))
+}
+
+/// Events that may occur in the range from `start` to `end`: single events overlapping it,
+/// and recurring events starting before its end.
+pub fn occurring(start: NaiveDateTime, end: Option<NaiveDateTime>) -> Condition {
+    Condition::any()
+        .add(overlapping(start, end).add(Column::RecurrenceRule.is_null()))
+        .add(
+            Condition::all()
+                .add(Column::RecurrenceRule.is_not_null())
+                .add_option(end.map(|end| Column::DateFrom.lt(end))),
+        )
+}
+
+/// Events shown to everyone: those without a category or in a public one. Editors see
+/// all events.
+pub fn visible(editor: bool) -> Condition {
+    if editor {
+        return Condition::all();
+    }
+
+    Condition::any().add(Column::CategoryId.is_null()).add(
+        Column::CategoryId.in_subquery(
+            Query::select()
+                .column(events_categories::Column::Id)
+                .from(EventsCategories)
+                .and_where(events_categories::Column::Public.eq(true))
+                .to_owned(),
+        ),
+    )
+}
+
+/// Events in one of `categories`, or all events if `None`.
+pub fn in_categories(categories: Option<&[u32]>) -> Condition {
+    Condition::all().add_option(
+        categories.map(|categories| Column::CategoryId.is_in(categories.iter().copied())),
+    )
+}
+
+/// The exceptions to the recurring events with the given IDs, by event ID.
+pub async fn find_exceptions<C: ConnectionTrait>(
+    db: &C,
+    ids: impl IntoIterator<Item = i32>,
+) -> Result<HashMap<i32, Vec<events_exceptions::Model>>, DbErr> {
+    let ids: Vec<_> = ids.into_iter().collect();
+
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut exceptions: HashMap<_, Vec<_>> = HashMap::new();
+
+    for exception in EventsExceptions::find()
+        .filter(events_exceptions::Column::EventsId.is_in(ids))
+        .all(db)
+        .await?
+    {
+        exceptions
+            .entry(exception.events_id)
+            .or_default()
+            .push(exception);
+    }
+
+    Ok(exceptions)
+}
+
+/// The recurrence of an event, or `None` if it happens once or its rule is invalid.
+pub fn series(
+    event: &events_data::Model,
+    exceptions: &mut HashMap<i32, Vec<events_exceptions::Model>>,
+) -> Option<Series> {
+    let rule = event.recurrence_rule.as_deref()?;
+
+    match rule.parse() {
+        Ok(rule) => Some(Series::new(
+            rule,
+            event.date_from,
+            event.date_to,
+            exceptions.remove(&event.id).unwrap_or_default(),
+        )),
+        Err(err) => {
+            tracing::warn!("Invalid recurrence rule of event {}: {err}", event.id);
+            None
+        }
+    }
+}
+
+/// Copy of a recurring event for one of its occurrences.
+pub fn occurrence_of(event: &events_data::Model, instance: Instance) -> events_data::Model {
+    events_data::Model {
+        date_from: instance.date_from,
+        date_to: instance.date_to,
+        title: instance.title.unwrap_or_else(|| event.title.clone()),
+        description: instance.description.or_else(|| event.description.clone()),
+        ..event.clone()
+    }
+}
+
+/// The public events not ended at `from`, soonest first, with recurring events expanded into
+/// their occurrences.
+pub async fn upcoming_events<C: ConnectionTrait>(
+    db: &C,
+    from: NaiveDateTime,
+    limit: u64,
+) -> Result<Vec<events_data::Model>, DbErr> {
+    let mut events = EventsData::find()
+        .filter(overlapping(from, None))
+        .filter(visible(false))
+        .filter(Column::RecurrenceRule.is_null())
+        .order_by_asc(Column::DateFrom)
+        .order_by_asc(Column::Id)
+        .limit(limit)
+        .all(db)
+        .await?;
+
+    let recurring = EventsData::find()
+        .filter(visible(false))
+        .filter(Column::RecurrenceRule.is_not_null())
+        .all(db)
+        .await?;
+
+    let mut exceptions = find_exceptions(db, recurring.iter().map(|event| event.id)).await?;
+
+    for event in &recurring {
+        match series(event, &mut exceptions) {
+            Some(series) => events.extend(
+                series
+                    .instances(from, None)
+                    .take(limit as usize)
+                    .map(|instance| occurrence_of(event, instance)),
+            ),
+            None if event.date_to >= from => events.push(event.clone()),
+            None => {}
+        }
+    }
+
+    events.sort_by_key(|event| (event.date_from, event.id));
+    events.truncate(limit as usize);
+
+    Ok(events)
+}
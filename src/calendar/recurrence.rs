@@ -0,0 +1,824 @@
+use super::{format_date, format_utc, local_to_utc, parse_time};
+use crate::entity::events_exceptions;
+use chrono::{Datelike, Duration, Months, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use std::{fmt::Write, str::FromStr};
+
+/// Number of periods searched for occurrences, so rules matching no dates end.
+const MAX_PERIODS: u32 = 100_000;
+
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+#[derive(thiserror::Error, Debug, PartialEq, Eq)]
+pub enum RecurrenceError {
+    #[error("Missing FREQ in recurrence rule")]
+    MissingFrequency,
+    #[error("Invalid recurrence rule part: {0}")]
+    InvalidPart(String),
+    #[error("Unsupported recurrence rule part: {0}")]
+    Unsupported(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Daily => "DAILY",
+            Self::Weekly => "WEEKLY",
+            Self::Monthly => "MONTHLY",
+            Self::Yearly => "YEARLY",
+        }
+    }
+}
+
+/// A recurrence rule in the RFC 5545 `RRULE` format, e.g. `FREQ=WEEKLY;BYDAY=MO,WE`.
+///
+/// The `FREQ`, `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY`, `BYMONTHDAY` and `BYMONTH` parts are
+/// supported, with weeks starting on Monday.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<NaiveDateTime>,
+    /// Weekdays, with an optional position in the month, e.g. `-1FR` for the last Friday.
+    by_day: Vec<(Option<i32>, Weekday)>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+}
+
+fn parse_list<T: FromStr>(
+    part: &str,
+    value: &str,
+    valid: impl Fn(&T) -> bool,
+) -> Result<Vec<T>, RecurrenceError> {
+    value
+        .split(',')
+        .map(|item| {
+            item.parse()
+                .ok()
+                .filter(&valid)
+                .ok_or_else(|| RecurrenceError::InvalidPart(part.to_owned()))
+        })
+        .collect()
+}
+
+fn parse_weekday(part: &str, item: &str) -> Result<(Option<i32>, Weekday), RecurrenceError> {
+    let invalid = || RecurrenceError::InvalidPart(part.to_owned());
+    let split = item.len().checked_sub(2).ok_or_else(invalid)?;
+    let (position, weekday) = item.split_at_checked(split).ok_or_else(invalid)?;
+
+    let weekday = WEEKDAYS
+        .iter()
+        .find(|(code, _)| *code == weekday)
+        .map(|(_, weekday)| *weekday)
+        .ok_or_else(invalid)?;
+
+    let position = if position.is_empty() {
+        None
+    } else {
+        Some(
+            position
+                .parse::<i32>()
+                .ok()
+                .filter(|position| *position != 0 && position.abs() <= 5)
+                .ok_or_else(invalid)?,
+        )
+    };
+
+    Ok((position, weekday))
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = RecurrenceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim().to_ascii_uppercase();
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: Vec::new(),
+            by_month_day: Vec::new(),
+            by_month: Vec::new(),
+        };
+
+        for part in s
+            .strip_prefix("RRULE:")
+            .unwrap_or(&s)
+            .split(';')
+            .filter(|part| !part.is_empty())
+        {
+            let invalid = || RecurrenceError::InvalidPart(part.to_owned());
+            let (name, value) = part.split_once('=').ok_or_else(invalid)?;
+
+            match name {
+                "FREQ" => {
+                    frequency = Some(match value {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(RecurrenceError::Unsupported(part.to_owned())),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(invalid)?
+                }
+                "COUNT" => rule.count = Some(value.parse().map_err(|_| invalid())?),
+                "UNTIL" => {
+                    let (until, date) = parse_time(value).ok_or_else(invalid)?;
+
+                    // A date includes all occurrences on that day.
+                    rule.until = Some(if date {
+                        until.date().and_time(NaiveTime::MIN) + Duration::days(1)
+                            - Duration::seconds(1)
+                    } else {
+                        until
+                    });
+                }
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|item| parse_weekday(part, item))
+                        .collect::<Result<_, _>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day =
+                        parse_list(part, value, |day: &i32| *day != 0 && day.abs() <= 31)?
+                }
+                "BYMONTH" => {
+                    rule.by_month = parse_list(part, value, |month| (1..=12).contains(month))?
+                }
+                "WKST" if value == "MO" => {}
+                _ => return Err(RecurrenceError::Unsupported(part.to_owned())),
+            }
+        }
+
+        rule.frequency = frequency.ok_or(RecurrenceError::MissingFrequency)?;
+
+        if rule.count.is_some() && rule.until.is_some() {
+            return Err(RecurrenceError::InvalidPart("COUNT".to_owned()));
+        }
+
+        let positioned = rule.by_day.iter().any(|(position, _)| position.is_some());
+
+        match rule.frequency {
+            Frequency::Daily | Frequency::Weekly if positioned => {
+                return Err(RecurrenceError::InvalidPart("BYDAY".to_owned()));
+            }
+            Frequency::Weekly if !rule.by_month_day.is_empty() => {
+                return Err(RecurrenceError::InvalidPart("BYMONTHDAY".to_owned()));
+            }
+            // Positions within the year are not supported, only within the month.
+            Frequency::Yearly if positioned && rule.by_month.is_empty() => {
+                return Err(RecurrenceError::Unsupported("BYDAY".to_owned()));
+            }
+            _ => {}
+        }
+
+        Ok(rule)
+    }
+}
+
+impl RecurrenceRule {
+    /// Format the rule for an event starting at a local time, or on a date if `all_day`.
+    ///
+    /// `UNTIL` is written in UTC for timed events, as RFC 5545 requires.
+    pub fn to_ics(&self, all_day: bool) -> String {
+        let mut rule = format!("FREQ={}", self.frequency.as_str());
+
+        if self.interval > 1 {
+            write!(rule, ";INTERVAL={}", self.interval).unwrap();
+        }
+
+        if let Some(count) = self.count {
+            write!(rule, ";COUNT={count}").unwrap();
+        }
+
+        if let Some(until) = self.until {
+            if all_day {
+                write!(rule, ";UNTIL={}", format_date(until.date())).unwrap();
+            } else {
+                write!(rule, ";UNTIL={}", format_utc(local_to_utc(until))).unwrap();
+            }
+        }
+
+        if !self.by_day.is_empty() {
+            let days: Vec<_> = self
+                .by_day
+                .iter()
+                .map(|(position, weekday)| {
+                    let code = WEEKDAYS.iter().find(|(_, day)| day == weekday).unwrap().0;
+
+                    match position {
+                        Some(position) => format!("{position}{code}"),
+                        None => code.to_owned(),
+                    }
+                })
+                .collect();
+
+            write!(rule, ";BYDAY={}", days.join(",")).unwrap();
+        }
+
+        if !self.by_month_day.is_empty() {
+            let days: Vec<_> = self.by_month_day.iter().map(i32::to_string).collect();
+
+            write!(rule, ";BYMONTHDAY={}", days.join(",")).unwrap();
+        }
+
+        if !self.by_month.is_empty() {
+            let months: Vec<_> = self.by_month.iter().map(u32::to_string).collect();
+
+            write!(rule, ";BYMONTH={}", months.join(",")).unwrap();
+        }
+
+        rule
+    }
+
+    /// Start times of the occurrences of a series whose first occurrence starts at `start`.
+    pub fn occurrences(&self, start: NaiveDateTime) -> Occurrences<'_> {
+        Occurrences {
+            rule: self,
+            start,
+            period: 0,
+            dates: Vec::new().into_iter(),
+            emitted: 0,
+        }
+    }
+
+    fn matches_month(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+
+    /// Dates matched in the month starting at `month`, for a series starting on `first`.
+    fn month_dates(&self, first: NaiveDate, month: NaiveDate) -> Vec<NaiveDate> {
+        let Some(next) = month.checked_add_months(Months::new(1)) else {
+            return Vec::new();
+        };
+
+        let length = (next - month).num_days() as i32;
+        let days = (0..length).map(|day| month + Duration::days(day.into()));
+
+        let mut dates: Vec<_> = if !self.by_month_day.is_empty() {
+            self.by_month_day
+                .iter()
+                .map(|day| if *day < 0 { length + day + 1 } else { *day })
+                .filter(|day| (1..=length).contains(day))
+                .map(|day| month + Duration::days((day - 1).into()))
+                .filter(|date| {
+                    self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|(_, weekday)| date.weekday() == *weekday)
+                })
+                .collect()
+        } else if !self.by_day.is_empty() {
+            self.by_day
+                .iter()
+                .flat_map(|(position, weekday)| {
+                    let matching: Vec<_> = days
+                        .clone()
+                        .filter(|date| date.weekday() == *weekday)
+                        .collect();
+
+                    match position {
+                        None => matching,
+                        Some(position) if *position > 0 => matching
+                            .get(*position as usize - 1)
+                            .copied()
+                            .into_iter()
+                            .collect(),
+                        Some(position) => matching
+                            .len()
+                            .checked_sub(position.unsigned_abs() as usize)
+                            .and_then(|index| matching.get(index))
+                            .copied()
+                            .into_iter()
+                            .collect(),
+                    }
+                })
+                .collect()
+        } else {
+            month.with_day(first.day()).into_iter().collect()
+        };
+
+        dates.sort();
+        dates.dedup();
+        dates
+    }
+
+    /// Dates matched in the `period`th period of a series starting on `first`, or `None`
+    /// past the supported dates.
+    fn dates(&self, first: NaiveDate, period: u32) -> Option<Vec<NaiveDate>> {
+        let step = period.checked_mul(self.interval)?;
+
+        let dates = match self.frequency {
+            Frequency::Daily => {
+                let date = first.checked_add_signed(Duration::days(step.into()))?;
+                let day = date.day() as i32;
+                let length = date
+                    .with_day(1)?
+                    .checked_add_months(Months::new(1))?
+                    .pred_opt()?
+                    .day() as i32;
+
+                let matches = self.matches_month(date)
+                    && (self.by_month_day.is_empty()
+                        || self
+                            .by_month_day
+                            .iter()
+                            .any(|d| *d == day || length + d + 1 == day))
+                    && (self.by_day.is_empty()
+                        || self
+                            .by_day
+                            .iter()
+                            .any(|(_, weekday)| date.weekday() == *weekday));
+
+                if matches { vec![date] } else { Vec::new() }
+            }
+            Frequency::Weekly => {
+                let monday = first
+                    .checked_sub_signed(Duration::days(
+                        first.weekday().num_days_from_monday().into(),
+                    ))?
+                    .checked_add_signed(Duration::try_weeks(step.into())?)?;
+
+                let mut dates: Vec<_> = if self.by_day.is_empty() {
+                    vec![first.weekday()]
+                } else {
+                    self.by_day.iter().map(|(_, weekday)| *weekday).collect()
+                }
+                .into_iter()
+                .filter_map(|weekday| {
+                    monday.checked_add_signed(Duration::days(weekday.num_days_from_monday().into()))
+                })
+                .filter(|date| self.matches_month(*date))
+                .collect();
+
+                dates.sort();
+                dates.dedup();
+                dates
+            }
+            Frequency::Monthly => {
+                let month = first.with_day(1)?.checked_add_months(Months::new(step))?;
+
+                if self.matches_month(month) {
+                    self.month_dates(first, month)
+                } else {
+                    Vec::new()
+                }
+            }
+            Frequency::Yearly => {
+                let year = first.year().checked_add(step.try_into().ok()?)?;
+                let mut months = if self.by_month.is_empty() {
+                    vec![first.month()]
+                } else {
+                    self.by_month.clone()
+                };
+
+                months.sort();
+
+                months
+                    .into_iter()
+                    .filter_map(|month| NaiveDate::from_ymd_opt(year, month, 1))
+                    .flat_map(|month| self.month_dates(first, month))
+                    .collect()
+            }
+        };
+
+        Some(dates)
+    }
+}
+
+/// Start times of the occurrences of a recurrence rule, in order.
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    start: NaiveDateTime,
+    period: u32,
+    dates: std::vec::IntoIter<NaiveDate>,
+    emitted: u32,
+}
+
+impl Iterator for Occurrences<'_> {
+    type Item = NaiveDateTime;
+
+    fn next(&mut self) -> Option<NaiveDateTime> {
+        loop {
+            if self.rule.count.is_some_and(|count| self.emitted >= count) {
+                return None;
+            }
+
+            if let Some(date) = self.dates.next() {
+                let time = date.and_time(self.start.time());
+
+                if time < self.start {
+                    continue;
+                }
+
+                if self.rule.until.is_some_and(|until| time > until) {
+                    self.period = MAX_PERIODS;
+                    self.dates = Vec::new().into_iter();
+                    return None;
+                }
+
+                self.emitted += 1;
+                return Some(time);
+            }
+
+            if self.period >= MAX_PERIODS {
+                return None;
+            }
+
+            self.dates = self.rule.dates(self.start.date(), self.period)?.into_iter();
+            self.period += 1;
+        }
+    }
+}
+
+/// A single occurrence of a recurring event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Instance {
+    /// Start of the occurrence according to the rule, identifying it in the series.
+    pub occurrence: NaiveDateTime,
+    pub date_from: NaiveDateTime,
+    pub date_to: NaiveDateTime,
+    /// Title replacing the one of the series.
+    pub title: Option<String>,
+    /// Description replacing the one of the series.
+    pub description: Option<String>,
+}
+
+/// A recurring event: its first occurrence, its rule and the exceptions to it.
+pub struct Series {
+    rule: RecurrenceRule,
+    date_from: NaiveDateTime,
+    duration: Duration,
+    exceptions: Vec<events_exceptions::Model>,
+}
+
+impl Series {
+    pub fn new(
+        rule: RecurrenceRule,
+        date_from: NaiveDateTime,
+        date_to: NaiveDateTime,
+        exceptions: Vec<events_exceptions::Model>,
+    ) -> Self {
+        Self {
+            rule,
+            date_from,
+            duration: date_to - date_from,
+            exceptions,
+        }
+    }
+
+    pub fn rule(&self) -> &RecurrenceRule {
+        &self.rule
+    }
+
+    pub fn exceptions(&self) -> &[events_exceptions::Model] {
+        &self.exceptions
+    }
+
+    /// The occurrence starting at `occurrence` according to the rule, with its exception
+    /// applied, or `None` if it was cancelled.
+    fn instance(&self, occurrence: NaiveDateTime) -> Option<Instance> {
+        let exception = self
+            .exceptions
+            .iter()
+            .find(|exception| exception.occurrence == occurrence);
+
+        match exception {
+            Some(exception) if exception.cancelled => None,
+            Some(exception) => Some(Instance {
+                occurrence,
+                date_from: exception.date_from.unwrap_or(occurrence),
+                date_to: exception.date_to.unwrap_or(occurrence + self.duration),
+                title: exception.title.clone(),
+                description: exception.description.clone(),
+            }),
+            None => Some(Instance {
+                occurrence,
+                date_from: occurrence,
+                date_to: occurrence + self.duration,
+                title: None,
+                description: None,
+            }),
+        }
+    }
+
+    /// The occurrences overlapping the range from `start` to `end`, or ending after `start`
+    /// if the range is open.
+    ///
+    /// Occurrences are in order of their start, with those moved into the range by an
+    /// exception merged in, so taking the first few gives the earliest ones.
+    pub fn instances(
+        &self,
+        start: NaiveDateTime,
+        end: Option<NaiveDateTime>,
+    ) -> impl Iterator<Item = Instance> + '_ {
+        let overlaps = move |from: NaiveDateTime, to: NaiveDateTime| {
+            to >= start && end.is_none_or(|end| from < end)
+        };
+
+        let mut moved: Vec<_> = self
+            .exceptions
+            .iter()
+            .filter(|exception| {
+                !exception.cancelled
+                    && !overlaps(exception.occurrence, exception.occurrence + self.duration)
+            })
+            .filter_map(|exception| self.instance(exception.occurrence))
+            .filter(|instance| overlaps(instance.date_from, instance.date_to))
+            .collect();
+
+        moved.sort_by_key(|instance| instance.date_from);
+
+        let mut moved = moved.into_iter().peekable();
+        let mut regular = self
+            .rule
+            .occurrences(self.date_from)
+            .skip_while(move |occurrence| *occurrence + self.duration < start)
+            .take_while(move |occurrence| end.is_none_or(|end| *occurrence < end))
+            .filter_map(|occurrence| self.instance(occurrence))
+            .filter(move |instance| overlaps(instance.date_from, instance.date_to))
+            .peekable();
+
+        std::iter::from_fn(move || {
+            let moved_first = match (moved.peek(), regular.peek()) {
+                (Some(moved), Some(regular)) => moved.date_from <= regular.date_from,
+                (moved, _) => moved.is_some(),
+            };
+
+            if moved_first {
+                moved.next()
+            } else {
+                regular.next()
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn occurrences(rule: &str, start: &str, limit: usize) -> Vec<NaiveDateTime> {
+        rule.parse::<RecurrenceRule>()
+            .unwrap()
+            .occurrences(time(start))
+            .take(limit)
+            .collect()
+    }
+
+    fn times(values: &[&str]) -> Vec<NaiveDateTime> {
+        values.iter().map(|value| time(value)).collect()
+    }
+
+    fn exception(
+        occurrence: &str,
+        date_from: Option<&str>,
+        cancelled: bool,
+    ) -> events_exceptions::Model {
+        events_exceptions::Model {
+            events_id: 1,
+            occurrence: time(occurrence),
+            cancelled,
+            date_from: date_from.map(time),
+            date_to: date_from.map(|date_from| time(date_from) + Duration::hours(1)),
+            title: None,
+            description: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rule: RecurrenceRule = "RRULE:freq=monthly;interval=2;byday=-1fr,2TU;WKST=MO"
+            .parse()
+            .unwrap();
+
+        assert_eq!(rule.frequency, Frequency::Monthly);
+        assert_eq!(rule.interval, 2);
+        assert_eq!(
+            rule.by_day,
+            [(Some(-1), Weekday::Fri), (Some(2), Weekday::Tue)]
+        );
+        assert_eq!(rule.to_ics(false), "FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR,2TU");
+    }
+
+    #[test]
+    fn parses_until_date_as_end_of_day() {
+        let rule: RecurrenceRule = "FREQ=DAILY;UNTIL=20250105".parse().unwrap();
+
+        assert_eq!(
+            rule.until,
+            Some(time("2025-01-05 23:59") + Duration::seconds(59))
+        );
+        assert_eq!(rule.to_ics(true), "FREQ=DAILY;UNTIL=20250105");
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let error = |rule: &str| rule.parse::<RecurrenceRule>().unwrap_err();
+
+        assert_eq!(error("INTERVAL=2"), RecurrenceError::MissingFrequency);
+        assert_eq!(
+            error("FREQ=HOURLY"),
+            RecurrenceError::Unsupported("FREQ=HOURLY".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=DAILY;INTERVAL=0"),
+            RecurrenceError::InvalidPart("INTERVAL=0".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=WEEKLY;BYDAY=1MO"),
+            RecurrenceError::InvalidPart("BYDAY".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=MONTHLY;BYDAY=6MO"),
+            RecurrenceError::InvalidPart("BYDAY=6MO".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=MONTHLY;BYMONTHDAY=0"),
+            RecurrenceError::InvalidPart("BYMONTHDAY=0".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=DAILY;COUNT=2;UNTIL=20250101"),
+            RecurrenceError::InvalidPart("COUNT".to_owned())
+        );
+        assert_eq!(
+            error("FREQ=YEARLY;BYDAY=1MO"),
+            RecurrenceError::Unsupported("BYDAY".to_owned())
+        );
+    }
+
+    #[test]
+    fn stops_after_count() {
+        assert_eq!(
+            occurrences("FREQ=DAILY;INTERVAL=2;COUNT=3", "2025-01-30 10:00", 10),
+            times(&["2025-01-30 10:00", "2025-02-01 10:00", "2025-02-03 10:00"])
+        );
+    }
+
+    #[test]
+    fn stops_after_until() {
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;UNTIL=20250115", "2025-01-01 18:00", 10),
+            times(&["2025-01-01 18:00", "2025-01-08 18:00", "2025-01-15 18:00"])
+        );
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;UNTIL=20250115T170000", "2025-01-01 18:00", 10),
+            times(&["2025-01-01 18:00", "2025-01-08 18:00"])
+        );
+    }
+
+    #[test]
+    fn expands_weekdays_from_the_start() {
+        // 1 January 2025 is a Wednesday, so the Monday of that week is skipped.
+        assert_eq!(
+            occurrences("FREQ=WEEKLY;BYDAY=MO,WE,FR", "2025-01-01 08:00", 4),
+            times(&[
+                "2025-01-01 08:00",
+                "2025-01-03 08:00",
+                "2025-01-06 08:00",
+                "2025-01-08 08:00",
+            ])
+        );
+    }
+
+    #[test]
+    fn expands_positioned_weekdays() {
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;BYDAY=2TU,-1FR", "2025-01-01 16:00", 4),
+            times(&[
+                "2025-01-14 16:00",
+                "2025-01-31 16:00",
+                "2025-02-11 16:00",
+                "2025-02-28 16:00",
+            ])
+        );
+        // Months without a fifth Monday are skipped.
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;BYDAY=5MO", "2025-01-01 09:00", 2),
+            times(&["2025-03-31 09:00", "2025-06-30 09:00"])
+        );
+    }
+
+    #[test]
+    fn expands_negative_month_days() {
+        assert_eq!(
+            occurrences("FREQ=MONTHLY;BYMONTHDAY=-1", "2024-01-31 12:00", 3),
+            times(&["2024-01-31 12:00", "2024-02-29 12:00", "2024-03-31 12:00"])
+        );
+        assert_eq!(
+            occurrences("FREQ=DAILY;BYMONTHDAY=1,-1", "2025-01-15 12:00", 3),
+            times(&["2025-01-31 12:00", "2025-02-01 12:00", "2025-02-28 12:00"])
+        );
+    }
+
+    #[test]
+    fn skips_missing_days_of_month() {
+        assert_eq!(
+            occurrences("FREQ=MONTHLY", "2025-01-31 10:00", 3),
+            times(&["2025-01-31 10:00", "2025-03-31 10:00", "2025-05-31 10:00"])
+        );
+        assert_eq!(
+            occurrences("FREQ=YEARLY;BYMONTH=3,9;BYDAY=-1SU", "2025-01-01 03:00", 3),
+            times(&["2025-03-30 03:00", "2025-09-28 03:00", "2026-03-29 03:00"])
+        );
+    }
+
+    #[test]
+    fn ends_rules_without_matches() {
+        assert_eq!(
+            occurrences("FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30", "2025-01-01 10:00", 1),
+            []
+        );
+    }
+
+    #[test]
+    fn applies_exceptions() {
+        let series = Series::new(
+            "FREQ=DAILY;COUNT=4".parse().unwrap(),
+            time("2025-01-01 10:00"),
+            time("2025-01-01 11:00"),
+            vec![
+                exception("2025-01-02 10:00", None, true),
+                exception("2025-01-03 10:00", Some("2025-01-03 14:00"), false),
+            ],
+        );
+
+        let starts: Vec<_> = series
+            .instances(time("2025-01-01 00:00"), None)
+            .map(|instance| (instance.occurrence, instance.date_from))
+            .collect();
+
+        assert_eq!(
+            starts,
+            [
+                (time("2025-01-01 10:00"), time("2025-01-01 10:00")),
+                (time("2025-01-03 10:00"), time("2025-01-03 14:00")),
+                (time("2025-01-04 10:00"), time("2025-01-04 10:00")),
+            ]
+        );
+    }
+
+    #[test]
+    fn merges_moved_occurrences_by_start() {
+        // Two occurrences from before the range are moved to its end.
+        let series = Series::new(
+            "FREQ=DAILY".parse().unwrap(),
+            time("2025-01-01 10:00"),
+            time("2025-01-01 11:00"),
+            vec![
+                exception("2025-01-02 10:00", Some("2025-01-20 10:00"), false),
+                exception("2025-01-01 10:00", Some("2025-01-15 12:00"), false),
+            ],
+        );
+
+        let starts: Vec<_> = series
+            .instances(time("2025-01-10 00:00"), Some(time("2025-01-21 00:00")))
+            .map(|instance| instance.date_from)
+            .collect();
+
+        let mut sorted = starts.clone();
+
+        sorted.sort();
+
+        assert_eq!(starts, sorted);
+        assert_eq!(starts.len(), 13);
+        assert_eq!(
+            starts[..2],
+            times(&["2025-01-10 10:00", "2025-01-11 10:00"])
+        );
+        assert_eq!(starts[6], time("2025-01-15 12:00"));
+        assert_eq!(starts[12], time("2025-01-20 10:00"));
+    }
+}
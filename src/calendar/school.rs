@@ -0,0 +1,190 @@
+use super::{is_weekend, public_holiday};
+use crate::entity::{
+    school_calendar_entries::{self, Entity as SchoolCalendarEntries},
+    school_years::{self, Entity as SchoolYears},
+};
+use async_graphql::Enum;
+use chrono::{Days, NaiveDate};
+use sea_orm::{ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder};
+
+/// How far ahead the next school day is searched for.
+const SEARCH_DAYS: u64 = 366;
+
+/// Why a day is or is not a school day.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SchoolDayKind {
+    /// A regular weekday with teaching.
+    Teaching,
+    /// A Saturday with teaching, in exchange for a bridge day.
+    WorkingSaturday,
+    /// A Saturday or Sunday.
+    Weekend,
+    /// A public holiday.
+    PublicHoliday,
+    /// A day of a school holiday, such as the winter or spring holiday.
+    Holiday,
+    /// A day without teaching set by the school.
+    Break,
+    /// A working day between a holiday and the weekend, made up on a working Saturday.
+    BridgeDay,
+    /// A day before the first or after the last day of the school year.
+    OutsideSchoolYear,
+}
+
+impl SchoolDayKind {
+    /// The kind of a school calendar entry, or `None` if it is unknown.
+    pub fn of_entry(kind: &str) -> Option<Self> {
+        match kind {
+            "holiday" => Some(Self::Holiday),
+            "break" => Some(Self::Break),
+            "bridge_day" => Some(Self::BridgeDay),
+            "working_saturday" => Some(Self::WorkingSaturday),
+            _ => None,
+        }
+    }
+
+    /// Precedence of entries covering the same day, highest first.
+    fn precedence(self) -> u8 {
+        match self {
+            Self::WorkingSaturday => 3,
+            Self::BridgeDay => 2,
+            Self::Holiday => 1,
+            _ => 0,
+        }
+    }
+
+    /// Whether there is teaching on days of this kind.
+    pub fn is_school_day(self) -> bool {
+        matches!(self, Self::Teaching | Self::WorkingSaturday)
+    }
+}
+
+/// A day in the school calendar.
+#[derive(Clone, Debug)]
+pub struct SchoolDate {
+    pub date: NaiveDate,
+    pub kind: SchoolDayKind,
+    /// Name of the holiday, break or special day.
+    ///
+    /// Public holidays are named outside the school year too.
+    pub name: Option<String>,
+    /// Term of the school year, 1 or 2.
+    pub term: Option<u8>,
+    pub school_year_id: Option<i32>,
+}
+
+impl SchoolDate {
+    pub fn is_school_day(&self) -> bool {
+        self.kind.is_school_day()
+    }
+}
+
+/// The school years and calendar entries of a range of dates.
+pub struct SchoolCalendar {
+    years: Vec<school_years::Model>,
+    entries: Vec<(SchoolDayKind, school_calendar_entries::Model)>,
+}
+
+impl SchoolCalendar {
+    /// Load the school calendar of the dates from `first` to `last`, inclusive.
+    pub async fn load<C: ConnectionTrait>(
+        db: &C,
+        first: NaiveDate,
+        last: NaiveDate,
+    ) -> Result<Self, DbErr> {
+        let years = SchoolYears::find()
+            .filter(school_years::Column::FirstDay.lte(last))
+            .filter(school_years::Column::LastDay.gte(first))
+            .order_by_asc(school_years::Column::FirstDay)
+            .all(db)
+            .await?;
+
+        let entries = SchoolCalendarEntries::find()
+            .filter(school_calendar_entries::Column::FirstDay.lte(last))
+            .filter(school_calendar_entries::Column::LastDay.gte(first))
+            .order_by_asc(school_calendar_entries::Column::FirstDay)
+            .all(db)
+            .await?
+            .into_iter()
+            .filter_map(|entry| match SchoolDayKind::of_entry(&entry.kind) {
+                Some(kind) => Some((kind, entry)),
+                None => {
+                    tracing::warn!(
+                        "Unknown kind of school calendar entry {}: {}",
+                        entry.id,
+                        entry.kind
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        Ok(Self { years, entries })
+    }
+
+    /// The school year `date` is in.
+    pub fn year(&self, date: NaiveDate) -> Option<&school_years::Model> {
+        self.years
+            .iter()
+            .find(|year| year.first_day <= date && date <= year.last_day)
+    }
+
+    /// Whether `date` is a school day, and why not.
+    ///
+    /// Entries of the school calendar take precedence over public holidays and weekends, so a
+    /// working Saturday is a school day and a bridge day is not.
+    pub fn day(&self, date: NaiveDate) -> SchoolDate {
+        let Some(year) = self.year(date) else {
+            return SchoolDate {
+                date,
+                kind: SchoolDayKind::OutsideSchoolYear,
+                name: public_holiday(date).map(str::to_owned),
+                term: None,
+                school_year_id: None,
+            };
+        };
+
+        let entry = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.first_day <= date && date <= entry.last_day)
+            .max_by_key(|(kind, _)| kind.precedence());
+
+        let (kind, name) = match entry {
+            Some((kind, entry)) => (*kind, entry.name.clone()),
+            None => match public_holiday(date) {
+                Some(name) => (SchoolDayKind::PublicHoliday, Some(name.to_owned())),
+                None if is_weekend(date) => (SchoolDayKind::Weekend, None),
+                None => (SchoolDayKind::Teaching, None),
+            },
+        };
+
+        SchoolDate {
+            date,
+            kind,
+            name,
+            term: Some(if date <= year.first_term_end { 1 } else { 2 }),
+            school_year_id: Some(year.id),
+        }
+    }
+}
+
+/// The first school day after `date`, if there is one within a year.
+pub async fn next_school_day<C: ConnectionTrait>(
+    db: &C,
+    date: NaiveDate,
+) -> Result<Option<SchoolDate>, DbErr> {
+    let Some(first) = date.checked_add_days(Days::new(1)) else {
+        return Ok(None);
+    };
+    let last = first
+        .checked_add_days(Days::new(SEARCH_DAYS))
+        .unwrap_or(NaiveDate::MAX);
+    let calendar = SchoolCalendar::load(db, first, last).await?;
+
+    Ok(first
+        .iter_days()
+        .take_while(|date| *date <= last)
+        .map(|date| calendar.day(date))
+        .find(SchoolDate::is_school_day))
+}
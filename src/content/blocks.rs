@@ -0,0 +1,525 @@
+use async_graphql::{Enum, SimpleObject, Union};
+use ego_tree::NodeRef;
+use scraper::{ElementRef, Html, Node};
+use url::Url;
+
+/// Inline formatting applied to a span of text.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mark {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Code,
+    Superscript,
+    Subscript,
+}
+
+/// A run of text with the same formatting.
+#[derive(SimpleObject, Clone, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// The text. Line breaks are represented as `\n`.
+    pub text: String,
+    /// Formatting applied to the text, in a stable order.
+    pub marks: Vec<Mark>,
+    /// Link target, if the text is a link.
+    pub href: Option<String>,
+}
+
+/// A paragraph of text.
+#[derive(SimpleObject, Debug)]
+pub struct ParagraphBlock {
+    pub spans: Vec<Span>,
+}
+
+/// A section heading.
+#[derive(SimpleObject, Debug)]
+pub struct HeadingBlock {
+    /// Heading level (1-6).
+    pub level: u8,
+    pub spans: Vec<Span>,
+}
+
+/// An image.
+#[derive(SimpleObject, Debug)]
+pub struct ImageBlock {
+    /// Resolved image URL.
+    pub url: String,
+    /// Alternative text.
+    pub alt: Option<String>,
+    /// Caption shown below the image.
+    pub caption: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// An item of a list.
+#[derive(SimpleObject, Debug)]
+pub struct ListItem {
+    pub spans: Vec<Span>,
+    /// Lists nested inside this item.
+    pub children: Vec<ListBlock>,
+}
+
+/// An ordered or unordered list.
+#[derive(SimpleObject, Debug)]
+pub struct ListBlock {
+    pub ordered: bool,
+    pub items: Vec<ListItem>,
+}
+
+/// A quotation.
+#[derive(SimpleObject, Debug)]
+pub struct QuoteBlock {
+    pub spans: Vec<Span>,
+}
+
+/// An embedded external player or map.
+#[derive(SimpleObject, Debug)]
+pub struct EmbedBlock {
+    /// URL of the embedded page.
+    pub url: String,
+    /// Host name of the embed provider, e.g. `www.youtube.com`.
+    pub provider: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// A table cell.
+#[derive(SimpleObject, Debug)]
+pub struct TableCell {
+    /// Whether the cell is a header cell.
+    pub header: bool,
+    pub spans: Vec<Span>,
+}
+
+/// A table row.
+#[derive(SimpleObject, Debug)]
+pub struct TableRow {
+    pub cells: Vec<TableCell>,
+}
+
+/// A table.
+#[derive(SimpleObject, Debug)]
+pub struct TableBlock {
+    pub rows: Vec<TableRow>,
+}
+
+/// Markup that has no structured representation.
+#[derive(SimpleObject, Debug)]
+pub struct RawHtmlBlock {
+    pub html: String,
+}
+
+/// A structural piece of post or page content.
+#[derive(Union, Debug)]
+pub enum Block {
+    Paragraph(ParagraphBlock),
+    Heading(HeadingBlock),
+    Image(ImageBlock),
+    List(ListBlock),
+    Quote(QuoteBlock),
+    Embed(EmbedBlock),
+    Table(TableBlock),
+    RawHtml(RawHtmlBlock),
+}
+
+/// Elements that only group other content and are flattened into their children.
+const CONTAINERS: [&str; 8] = [
+    "div", "section", "article", "main", "header", "footer", "center", "body",
+];
+
+const INLINE: [&str; 20] = [
+    "a", "abbr", "b", "bdi", "bdo", "cite", "code", "em", "font", "i", "kbd", "mark", "q", "s",
+    "small", "span", "strike", "strong", "sub", "sup",
+];
+
+pub(super) fn is_inline(name: &str) -> bool {
+    INLINE.contains(&name) || matches!(name, "u" | "del" | "ins" | "br" | "tt" | "var" | "time")
+}
+
+fn mark(name: &str) -> Option<Mark> {
+    Some(match name {
+        "b" | "strong" => Mark::Bold,
+        "i" | "em" | "cite" => Mark::Italic,
+        "u" | "ins" => Mark::Underline,
+        "s" | "strike" | "del" => Mark::Strikethrough,
+        "code" | "kbd" | "tt" => Mark::Code,
+        "sup" => Mark::Superscript,
+        "sub" => Mark::Subscript,
+        _ => return None,
+    })
+}
+
+fn dimension(element: &ElementRef, attribute: &str) -> Option<u32> {
+    element.value().attr(attribute)?.trim().parse().ok()
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_owned)
+}
+
+/// Collects inline content into spans, collapsing whitespace like a browser would.
+#[derive(Default)]
+struct SpanBuilder {
+    spans: Vec<Span>,
+    marks: Vec<Mark>,
+    href: Option<String>,
+}
+
+impl SpanBuilder {
+    fn push_text(&mut self, text: &str) {
+        let ends_with_space = |spans: &[Span]| {
+            spans
+                .last()
+                .is_none_or(|span| span.text.ends_with([' ', '\n']))
+        };
+
+        let mut collapsed = String::with_capacity(text.len());
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                let redundant = collapsed.ends_with(' ')
+                    || (collapsed.is_empty() && ends_with_space(&self.spans));
+
+                if !redundant {
+                    collapsed.push(' ');
+                }
+            } else {
+                collapsed.push(c);
+            }
+        }
+
+        self.push(collapsed);
+    }
+
+    fn push(&mut self, text: String) {
+        if text.is_empty() {
+            return;
+        }
+
+        let mut marks = self.marks.clone();
+        marks.sort();
+        marks.dedup();
+
+        match self.spans.last_mut() {
+            Some(last) if last.marks == marks && last.href == self.href => {
+                last.text.push_str(&text)
+            }
+            _ => self.spans.push(Span {
+                text,
+                marks,
+                href: self.href.clone(),
+            }),
+        }
+    }
+
+    fn line_break(&mut self) {
+        if let Some(last) = self.spans.last_mut() {
+            last.text.truncate(last.text.trim_end_matches(' ').len());
+        }
+
+        self.push("\n".to_owned());
+    }
+
+    fn visit(&mut self, node: NodeRef<Node>) {
+        match node.value() {
+            Node::Text(text) => self.push_text(text),
+            Node::Element(element) => {
+                let name = element.name();
+
+                if name == "br" {
+                    self.line_break();
+                    return;
+                }
+
+                // Separate nested block elements, e.g. paragraphs inside a quote.
+                if !is_inline(name)
+                    && self
+                        .spans
+                        .last()
+                        .is_some_and(|span| !span.text.ends_with('\n'))
+                {
+                    self.line_break();
+                }
+
+                let mark = mark(name);
+                let previous_href = self.href.clone();
+
+                if let Some(mark) = mark {
+                    self.marks.push(mark);
+                }
+
+                if name == "a" {
+                    self.href = non_empty(element.attr("href"));
+                }
+
+                for child in node.children() {
+                    self.visit(child);
+                }
+
+                if mark.is_some() {
+                    self.marks.pop();
+                }
+
+                self.href = previous_href;
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> Vec<Span> {
+        if let Some(first) = self.spans.first_mut() {
+            first.text = first.text.trim_start().to_owned();
+        }
+
+        if let Some(last) = self.spans.last_mut() {
+            last.text = last.text.trim_end().to_owned();
+        }
+
+        self.spans.retain(|span| !span.text.is_empty());
+        self.spans
+    }
+}
+
+fn spans(element: ElementRef) -> Vec<Span> {
+    let mut builder = SpanBuilder::default();
+
+    for child in element.children() {
+        builder.visit(child);
+    }
+
+    builder.finish()
+}
+
+fn image(element: ElementRef, caption: Option<String>) -> Option<Block> {
+    Some(Block::Image(ImageBlock {
+        url: non_empty(element.value().attr("src"))?,
+        alt: non_empty(element.value().attr("alt")),
+        caption,
+        width: dimension(&element, "width"),
+        height: dimension(&element, "height"),
+    }))
+}
+
+fn embed(element: ElementRef) -> Option<Block> {
+    let url = non_empty(element.value().attr("src"))?;
+    let provider = Url::parse(&url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_owned));
+
+    Some(Block::Embed(EmbedBlock {
+        url,
+        provider,
+        width: dimension(&element, "width"),
+        height: dimension(&element, "height"),
+    }))
+}
+
+fn list(element: ElementRef) -> ListBlock {
+    let items = element
+        .child_elements()
+        .filter(|child| child.value().name() == "li")
+        .map(|item| {
+            let mut builder = SpanBuilder::default();
+            let mut children = Vec::new();
+
+            for child in item.children() {
+                match ElementRef::wrap(child) {
+                    Some(nested) if matches!(nested.value().name(), "ul" | "ol") => {
+                        children.push(list(nested))
+                    }
+                    _ => builder.visit(child),
+                }
+            }
+
+            ListItem {
+                spans: builder.finish(),
+                children,
+            }
+        })
+        .collect();
+
+    ListBlock {
+        ordered: element.value().name() == "ol",
+        items,
+    }
+}
+
+fn table(element: ElementRef) -> TableBlock {
+    let mut rows = Vec::new();
+    let mut stack = vec![element];
+
+    while let Some(element) = stack.pop() {
+        for child in element
+            .child_elements()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+        {
+            match child.value().name() {
+                "thead" | "tbody" | "tfoot" => stack.push(child),
+                "tr" => rows.push(child),
+                _ => {}
+            }
+        }
+    }
+
+    let mut rows: Vec<_> = rows
+        .into_iter()
+        .map(|row| TableRow {
+            cells: row
+                .child_elements()
+                .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+                .map(|cell| TableCell {
+                    header: cell.value().name() == "th",
+                    spans: spans(cell),
+                })
+                .collect(),
+        })
+        .collect();
+
+    rows.retain(|row| !row.cells.is_empty());
+
+    TableBlock { rows }
+}
+
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    paragraph: SpanBuilder,
+}
+
+impl BlockBuilder {
+    fn flush_paragraph(&mut self) {
+        let spans = std::mem::take(&mut self.paragraph).finish();
+
+        if !spans.is_empty() {
+            self.blocks.push(Block::Paragraph(ParagraphBlock { spans }));
+        }
+    }
+
+    fn push(&mut self, block: Block) {
+        self.flush_paragraph();
+        self.blocks.push(block);
+    }
+
+    fn raw(&mut self, element: ElementRef) {
+        self.push(Block::RawHtml(RawHtmlBlock {
+            html: element.html(),
+        }));
+    }
+
+    /// A paragraph, with images and embeds inside it lifted out into their own blocks.
+    fn paragraph(&mut self, element: ElementRef) {
+        self.flush_paragraph();
+
+        let mut lifted = Vec::new();
+
+        for descendant in element.descendants().filter_map(ElementRef::wrap) {
+            match descendant.value().name() {
+                "img" => lifted.extend(image(descendant, None)),
+                "iframe" => lifted.extend(embed(descendant)),
+                _ => {}
+            }
+        }
+
+        for child in element.children() {
+            self.paragraph.visit(child);
+        }
+
+        self.flush_paragraph();
+        self.blocks.extend(lifted);
+    }
+
+    fn visit(&mut self, node: NodeRef<Node>) {
+        let Some(element) = ElementRef::wrap(node) else {
+            if let Node::Text(_) = node.value() {
+                self.paragraph.visit(node);
+            }
+
+            return;
+        };
+
+        let name = element.value().name();
+
+        match name {
+            _ if CONTAINERS.contains(&name) => {
+                self.flush_paragraph();
+
+                for child in element.children() {
+                    self.visit(child);
+                }
+
+                self.flush_paragraph();
+            }
+            _ if is_inline(name) => self.paragraph.visit(node),
+            "p" => self.paragraph(element),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let spans = spans(element);
+
+                if !spans.is_empty() {
+                    self.push(Block::Heading(HeadingBlock {
+                        level: name[1..].parse().unwrap(),
+                        spans,
+                    }));
+                }
+            }
+            "img" => match image(element, None) {
+                Some(block) => self.push(block),
+                None => self.flush_paragraph(),
+            },
+            "iframe" => match embed(element) {
+                Some(block) => self.push(block),
+                None => self.flush_paragraph(),
+            },
+            "figure" => {
+                let caption = element
+                    .child_elements()
+                    .find(|child| child.value().name() == "figcaption")
+                    .map(|caption| spans(caption).into_iter().map(|span| span.text).collect())
+                    .filter(|caption: &String| !caption.is_empty());
+
+                let block = element
+                    .descendants()
+                    .filter_map(ElementRef::wrap)
+                    .find_map(|child| match child.value().name() {
+                        "img" => image(child, caption.clone()),
+                        "iframe" => embed(child),
+                        _ => None,
+                    });
+
+                match block {
+                    Some(block) => self.push(block),
+                    None => self.raw(element),
+                }
+            }
+            "ul" | "ol" => self.push(Block::List(list(element))),
+            "blockquote" => self.push(Block::Quote(QuoteBlock {
+                spans: spans(element),
+            })),
+            "table" => self.push(Block::Table(table(element))),
+            _ => self.raw(element),
+        }
+    }
+}
+
+/// Split HTML content into a list of structured blocks.
+///
+/// The same input always yields the same blocks.
+pub fn parse_blocks(html: &str) -> Vec<Block> {
+    let fragment = Html::parse_fragment(html);
+    let mut builder = BlockBuilder {
+        blocks: Vec::new(),
+        paragraph: SpanBuilder::default(),
+    };
+
+    for child in fragment.root_element().children() {
+        builder.visit(child);
+    }
+
+    builder.flush_paragraph();
+    builder.blocks
+}
@@ -0,0 +1,58 @@
+use crate::{Config, graphql::Locale};
+use chrono::NaiveDateTime;
+use lru::LruCache;
+use std::{num::NonZeroUsize, sync::Mutex};
+
+/// The kind of record a piece of rendered content belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContentKind {
+    Post,
+    Page,
+}
+
+type Key = (ContentKind, u32, Locale);
+
+/// In-memory cache of rendered content, invalidated by the record's `updated_at`.
+///
+/// Holds at most `CONTENT_CACHE_SIZE` renderings, evicting the least recently used.
+pub struct ContentCache {
+    entries: Mutex<LruCache<Key, (NaiveDateTime, String)>>,
+}
+
+impl ContentCache {
+    pub fn new(config: &Config) -> Self {
+        let capacity = NonZeroUsize::new(config.content_cache_size).unwrap_or(NonZeroUsize::MIN);
+
+        Self {
+            entries: Mutex::new(LruCache::new(capacity)),
+        }
+    }
+
+    /// Return the cached rendering of a record, or render and store it if the
+    /// record has been updated since it was cached.
+    pub fn get_or_render(
+        &self,
+        kind: ContentKind,
+        id: u32,
+        locale: &Locale,
+        updated_at: NaiveDateTime,
+        render: impl FnOnce() -> String,
+    ) -> String {
+        let key = (kind, id, locale.clone());
+
+        if let Some((cached_at, content)) = self.entries.lock().unwrap().get(&key)
+            && *cached_at == updated_at
+        {
+            return content.clone();
+        }
+
+        let content = render();
+
+        self.entries
+            .lock()
+            .unwrap()
+            .put(key, (updated_at, content.clone()));
+
+        content
+    }
+}
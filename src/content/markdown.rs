@@ -0,0 +1,18 @@
+use comrak::{Options, markdown_to_html};
+
+/// Render Markdown (CommonMark with tables, footnotes, strikethrough and
+/// autolinks) to HTML.
+///
+/// Raw HTML is passed through, as the output is sanitised afterwards like any
+/// other HTML content.
+pub fn render_markdown(markdown: &str) -> String {
+    let mut options = Options::default();
+
+    options.extension.table = true;
+    options.extension.footnotes = true;
+    options.extension.strikethrough = true;
+    options.extension.autolink = true;
+    options.render.r#unsafe = true;
+
+    markdown_to_html(markdown, &options)
+}
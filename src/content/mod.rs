@@ -0,0 +1,17 @@
+mod blocks;
+mod cache;
+mod markdown;
+mod render;
+mod sanitize;
+mod shortcodes;
+mod text;
+mod urls;
+
+pub use blocks::*;
+pub use cache::*;
+pub use markdown::*;
+pub use render::*;
+pub use sanitize::*;
+pub use shortcodes::*;
+pub use text::*;
+pub use urls::*;
@@ -0,0 +1,26 @@
+use super::{Sanitizer, UrlRewriter, render_markdown};
+use crate::{Config, entity::sea_orm_active_enums::ContentFormat};
+
+/// Turns stored post and page content into the HTML served to clients.
+pub struct ContentRenderer {
+    sanitizer: Sanitizer,
+    urls: UrlRewriter,
+}
+
+impl ContentRenderer {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            sanitizer: Sanitizer::new(config),
+            urls: UrlRewriter::new(config),
+        }
+    }
+
+    pub fn render(&self, content: &str, format: ContentFormat) -> String {
+        let html = match format {
+            ContentFormat::Html => self.sanitizer.clean(content),
+            ContentFormat::Markdown => self.sanitizer.clean(&render_markdown(content)),
+        };
+
+        self.urls.rewrite(&html)
+    }
+}
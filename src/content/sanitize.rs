@@ -0,0 +1,142 @@
+use crate::Config;
+use ammonia::Builder;
+use std::sync::Arc;
+use url::Url;
+
+const IFRAME_ATTRIBUTES: [&str; 7] = [
+    "src",
+    "width",
+    "height",
+    "title",
+    "allow",
+    "allowfullscreen",
+    "frameborder",
+];
+
+fn split_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Whether `src` points to one of the approved embed locations.
+///
+/// Each entry of `embed_hosts` is a host optionally followed by a path prefix,
+/// e.g. `www.youtube.com/embed/`.
+fn is_allowed_embed(embed_hosts: &[String], src: &str) -> bool {
+    let src = match src.strip_prefix("//") {
+        Some(src) => format!("https://{src}"),
+        None => src.to_owned(),
+    };
+
+    let Ok(url) = Url::parse(&src) else {
+        return false;
+    };
+
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+
+    let location = format!("{host}{}", url.path());
+
+    url.scheme() == "https"
+        && embed_hosts
+            .iter()
+            .any(|allowed| match allowed.split_once('/') {
+                Some((allowed_host, _)) => {
+                    host == allowed_host && location.starts_with(allowed.as_str())
+                }
+                None => host == allowed,
+            })
+}
+
+/// Removes anything from user-provided HTML that is not on the configured allowlists.
+///
+/// Lists left unconfigured use ammonia's defaults. Iframes are always allowed, but
+/// only keep their `src` when it points to an approved embed host. Link `rel`
+/// attributes are left to [`UrlRewriter`](super::UrlRewriter).
+pub struct Sanitizer {
+    tags: Option<Vec<String>>,
+    generic_attributes: Option<Vec<String>>,
+    tag_attributes: Option<Vec<(String, String)>>,
+    url_schemes: Option<Vec<String>>,
+    embed_hosts: Arc<[String]>,
+}
+
+impl Sanitizer {
+    pub fn new(config: &Config) -> Self {
+        let (tag_attributes, generic_attributes) = match config.sanitize_attributes {
+            Some(ref attributes) => {
+                let (tag, generic): (Vec<_>, Vec<_>) = split_list(attributes)
+                    .into_iter()
+                    .partition(|attribute| attribute.contains(':'));
+
+                let tag = tag
+                    .into_iter()
+                    .filter_map(|attribute| {
+                        let (tag, attribute) = attribute.split_once(':')?;
+                        Some((tag.to_owned(), attribute.to_owned()))
+                    })
+                    .collect();
+
+                (Some(tag), Some(generic))
+            }
+            None => (None, None),
+        };
+
+        Self {
+            tags: config.sanitize_tags.as_deref().map(split_list),
+            generic_attributes,
+            tag_attributes,
+            url_schemes: config.sanitize_url_schemes.as_deref().map(split_list),
+            embed_hosts: split_list(&config.embed_hosts).into(),
+        }
+    }
+
+    pub fn clean(&self, html: &str) -> String {
+        let mut builder = Builder::default();
+
+        if let Some(ref tags) = self.tags {
+            builder
+                .tags(tags.iter().map(String::as_str).collect())
+                .rm_clean_content_tags(tags.iter().map(String::as_str));
+        }
+
+        if let Some(ref attributes) = self.generic_attributes {
+            builder.generic_attributes(attributes.iter().map(String::as_str).collect());
+        }
+
+        if let Some(ref attributes) = self.tag_attributes {
+            builder.tag_attributes(Default::default());
+
+            for (tag, attribute) in attributes {
+                builder.add_tag_attributes(tag.as_str(), [attribute.as_str()]);
+            }
+        }
+
+        if let Some(ref schemes) = self.url_schemes {
+            builder.url_schemes(schemes.iter().map(String::as_str).collect());
+        }
+
+        let embed_hosts = Arc::clone(&self.embed_hosts);
+
+        builder
+            .link_rel(None)
+            .add_tags(["iframe"])
+            .add_tag_attributes("iframe", IFRAME_ATTRIBUTES)
+            .attribute_filter(move |element, attribute, value| {
+                if element == "iframe"
+                    && attribute == "src"
+                    && !is_allowed_embed(&embed_hosts, value)
+                {
+                    None
+                } else {
+                    Some(value.into())
+                }
+            })
+            .clean(html)
+            .to_string()
+    }
+}
@@ -0,0 +1,497 @@
+use crate::{
+    Config,
+    calendar::{upcoming_events, visible},
+    entity::{
+        canteen_data,
+        canteen_menus::{self, Entity as CanteenMenus},
+        canteen_pivot_menus_data,
+        colleagues_data::Entity as ColleaguesData,
+        events_data::{self, Entity as EventsData},
+    },
+    graphql::resolvers::Image,
+    utils::db_error,
+};
+use async_graphql::Result;
+use chrono::{Datelike, Local, NaiveDate, NaiveDateTime, Weekday};
+use sea_orm::{
+    DatabaseTransaction, JoinType,
+    prelude::*,
+    query::{Order, QueryOrder, QuerySelect},
+};
+use std::{collections::BTreeMap, fmt::Write, ops::Range};
+
+/// Names of the shortcodes [`ShortcodeExpander`] knows how to expand.
+const SHORTCODES: [&str; 4] = ["event", "colleague", "gallery", "canteen"];
+
+const DEFAULT_EVENT_LIMIT: u64 = 5;
+const MAX_EVENT_LIMIT: u64 = 50;
+
+/// A `[name key=value key="quoted value"]` tag found in content.
+#[derive(Debug)]
+struct Shortcode<'a> {
+    name: &'a str,
+    attributes: BTreeMap<&'a str, &'a str>,
+    range: Range<usize>,
+    /// Written with doubled brackets, to be output literally with single ones.
+    escaped: bool,
+}
+
+impl Shortcode<'_> {
+    fn number<T: std::str::FromStr>(&self, attribute: &str) -> Option<T> {
+        self.attributes.get(attribute)?.parse().ok()
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+/// Parse the shortcode starting at the `[` at `start`.
+fn parse_at(html: &str, start: usize) -> Option<Shortcode<'_>> {
+    let end = start + html[start..].find(']')?;
+    let inner = &html[start + 1..end];
+
+    let name_end = inner.find(|c| !is_name_char(c)).unwrap_or(inner.len());
+    let name = &inner[..name_end];
+
+    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let rest = &inner[name_end..];
+
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut attributes = BTreeMap::new();
+    let mut rest = rest.trim_start();
+
+    while !rest.is_empty() {
+        let (key, value) = rest.split_once('=')?;
+
+        if key.is_empty() || !key.chars().all(is_name_char) {
+            return None;
+        }
+
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let (value, remaining) = quoted.split_once('"')?;
+                (value, remaining)
+            }
+            None => value.split_at(value.find(' ').unwrap_or(value.len())),
+        };
+
+        attributes.insert(key, value);
+        rest = remaining.trim_start();
+    }
+
+    Some(Shortcode {
+        name,
+        attributes,
+        range: start..end + 1,
+        escaped: false,
+    })
+}
+
+/// End of the tag starting at the `<` at `start`, skipping `>` in quoted attribute values.
+fn tag_end(html: &str, start: usize) -> usize {
+    let mut quote = None;
+
+    for (index, c) in html[start..].char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '>') => return start + index + 1,
+            _ => {}
+        }
+    }
+
+    html.len()
+}
+
+/// Byte ranges of the text between the tags and comments of `html`.
+fn text_ranges(html: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut position = 0;
+
+    while let Some(offset) = html[position..].find('<') {
+        let start = position + offset;
+
+        ranges.push(position..start);
+        position = if html[start..].starts_with("<!--") {
+            html[start..]
+                .find("-->")
+                .map_or(html.len(), |end| start + end + "-->".len())
+        } else {
+            tag_end(html, start)
+        };
+    }
+
+    ranges.push(position..html.len());
+    ranges
+}
+
+/// Find all shortcodes in `html`, in order.
+///
+/// Only text is searched, so shortcodes in attribute values are left alone. A doubled
+/// bracket (`[[gallery]]`) escapes a shortcode.
+fn parse(html: &str) -> Vec<Shortcode<'_>> {
+    text_ranges(html)
+        .into_iter()
+        .flat_map(|range| parse_text(&html[..range.end], range.start))
+        .collect()
+}
+
+/// Find the shortcodes in the text of `html` from `position` to its end.
+fn parse_text(html: &str, mut position: usize) -> Vec<Shortcode<'_>> {
+    let mut shortcodes = Vec::new();
+
+    while let Some(offset) = html[position..].find('[') {
+        let start = position + offset;
+
+        if html[start + 1..].starts_with('[') {
+            match parse_at(html, start + 1) {
+                Some(mut shortcode) if html[shortcode.range.end..].starts_with(']') => {
+                    shortcode.range = start..shortcode.range.end + 1;
+                    shortcode.escaped = true;
+                    position = shortcode.range.end;
+                    shortcodes.push(shortcode);
+                }
+                _ => position = start + 2,
+            }
+
+            continue;
+        }
+
+        match parse_at(html, start) {
+            Some(shortcode) => {
+                position = shortcode.range.end;
+                shortcodes.push(shortcode);
+            }
+            None => position = start + 1,
+        }
+    }
+
+    shortcodes
+}
+
+/// Remove known shortcodes from `html`, e.g. before extracting its text.
+///
+/// Escaped shortcodes are kept as literal text.
+pub fn strip_shortcodes(html: &str) -> String {
+    let mut stripped = String::with_capacity(html.len());
+    let mut position = 0;
+
+    for shortcode in parse(html) {
+        let range = shortcode.range;
+
+        if shortcode.escaped {
+            stripped.push_str(&html[position..range.start]);
+            stripped.push_str(&html[range.start + 1..range.end - 1]);
+        } else if SHORTCODES.contains(&shortcode.name) {
+            stripped.push_str(&html[position..range.start]);
+        } else {
+            continue;
+        }
+
+        position = range.end;
+    }
+
+    stripped.push_str(&html[position..]);
+    stripped
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn format_date_range(from: NaiveDateTime, to: NaiveDateTime) -> String {
+    if from.date() == to.date() {
+        format!(
+            "{} – {}",
+            from.format("%Y. %m. %d. %H:%M"),
+            to.format("%H:%M")
+        )
+    } else {
+        format!(
+            "{} – {}",
+            from.format("%Y. %m. %d. %H:%M"),
+            to.format("%Y. %m. %d. %H:%M")
+        )
+    }
+}
+
+fn event_html(html: &mut String, event: &events_data::Model) {
+    write!(
+        html,
+        "<div class=\"shortcode-event\"><h4>{}</h4><p><time>{}</time></p>",
+        escape(&event.title),
+        format_date_range(event.date_from, event.date_to),
+    )
+    .unwrap();
+
+    if let Some(ref description) = event.description {
+        write!(html, "<p>{}</p>", escape(description)).unwrap();
+    }
+
+    html.push_str("</div>");
+}
+
+/// Expands shortcodes in rendered content into HTML.
+pub struct ShortcodeExpander<'a> {
+    db: &'a DatabaseTransaction,
+    config: &'a Config,
+    /// Images for `[gallery]`, if the content has any.
+    gallery: Option<&'a [Image]>,
+}
+
+impl<'a> ShortcodeExpander<'a> {
+    pub fn new(db: &'a DatabaseTransaction, config: &'a Config) -> Self {
+        Self {
+            db,
+            config,
+            gallery: None,
+        }
+    }
+
+    pub fn with_gallery(mut self, images: &'a [Image]) -> Self {
+        self.gallery = Some(images);
+        self
+    }
+
+    /// Replace the known shortcodes in `html`.
+    ///
+    /// Unknown shortcodes, and ones referring to missing records, are left intact.
+    pub async fn expand(&self, html: &str) -> Result<String> {
+        let shortcodes = parse(html);
+
+        if shortcodes.is_empty() {
+            return Ok(html.to_owned());
+        }
+
+        let mut expanded = String::with_capacity(html.len());
+        let mut position = 0;
+
+        for shortcode in shortcodes {
+            if shortcode.escaped {
+                let range = shortcode.range;
+
+                expanded.push_str(&html[position..range.start]);
+                expanded.push_str(&html[range.start + 1..range.end - 1]);
+                position = range.end;
+                continue;
+            }
+
+            let replacement = match shortcode.name {
+                "event" => self.event(&shortcode).await?,
+                "colleague" => self.colleague(&shortcode).await?,
+                "gallery" => self.gallery(),
+                "canteen" => self.canteen(&shortcode).await?,
+                _ => None,
+            };
+
+            if let Some(replacement) = replacement {
+                let mut range = shortcode.range;
+
+                // The widgets are block-level, so replace a paragraph holding only the shortcode.
+                if html[position..range.start].ends_with("<p>")
+                    && html[range.end..].starts_with("</p>")
+                {
+                    range = range.start - "<p>".len()..range.end + "</p>".len();
+                }
+
+                expanded.push_str(&html[position..range.start]);
+                expanded.push_str(&replacement);
+                position = range.end;
+            }
+        }
+
+        expanded.push_str(&html[position..]);
+
+        Ok(expanded)
+    }
+
+    /// `[event id=42]` for a single event, or `[event limit=5]` for the upcoming ones.
+    async fn event(&self, shortcode: &Shortcode<'_>) -> Result<Option<String>> {
+        let mut html = String::new();
+
+        if let Some(id) = shortcode.number::<u32>("id") {
+            let Some(event) = EventsData::find_by_id(id as i32)
+                .filter(visible(false))
+                .one(self.db)
+                .await
+                .map_err(db_error)?
+            else {
+                return Ok(None);
+            };
+
+            event_html(&mut html, &event);
+        } else {
+            let limit = shortcode
+                .number("limit")
+                .unwrap_or(DEFAULT_EVENT_LIMIT)
+                .min(MAX_EVENT_LIMIT);
+
+            let events = upcoming_events(self.db, Local::now().naive_local(), limit)
+                .await
+                .map_err(db_error)?;
+
+            html.push_str("<div class=\"shortcode-events\">");
+
+            for event in &events {
+                event_html(&mut html, event);
+            }
+
+            html.push_str("</div>");
+        }
+
+        Ok(Some(html))
+    }
+
+    /// `[colleague id=7]`
+    async fn colleague(&self, shortcode: &Shortcode<'_>) -> Result<Option<String>> {
+        let Some(id) = shortcode.number::<u32>("id") else {
+            return Ok(None);
+        };
+
+        let Some(colleague) = ColleaguesData::find_by_id(id as i32)
+            .one(self.db)
+            .await
+            .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        let mut html = String::from("<div class=\"shortcode-colleague\">");
+
+        if let Some(ref image) = colleague.image {
+            write!(
+                html,
+                "<img src=\"{}/colleagues_images/{}\" alt=\"{}\">",
+                self.config.storage_base_url,
+                escape(image),
+                escape(colleague.name.as_deref().unwrap_or_default()),
+            )
+            .unwrap();
+        }
+
+        if let Some(ref name) = colleague.name {
+            write!(html, "<h4>{}</h4>", escape(name)).unwrap();
+        }
+
+        for detail in [&colleague.jobs, &colleague.subjects].into_iter().flatten() {
+            write!(html, "<p>{}</p>", escape(detail)).unwrap();
+        }
+
+        html.push_str("</div>");
+
+        Ok(Some(html))
+    }
+
+    /// `[gallery]`
+    fn gallery(&self) -> Option<String> {
+        let images = self.gallery?;
+        let mut html = String::from("<div class=\"shortcode-gallery\">");
+
+        for image in images {
+            write!(
+                html,
+                "<figure><img src=\"{}\" alt=\"{}\">",
+                escape(&image.url),
+                escape(image.alt.as_deref().unwrap_or_default()),
+            )
+            .unwrap();
+
+            if let Some(ref caption) = image.caption {
+                write!(html, "<figcaption>{}</figcaption>", escape(caption)).unwrap();
+            }
+
+            html.push_str("</figure>");
+        }
+
+        html.push_str("</div>");
+
+        Some(html)
+    }
+
+    /// `[canteen week=current]`, where `week` is `current`, `next` or an ISO week
+    /// number of the current year.
+    async fn canteen(&self, shortcode: &Shortcode<'_>) -> Result<Option<String>> {
+        let today = Local::now().date_naive();
+
+        let monday = match shortcode.attributes.get("week").copied() {
+            None | Some("current") => today.week(Weekday::Mon).first_day(),
+            Some("next") => today.week(Weekday::Mon).first_day() + chrono::Duration::weeks(1),
+            Some(week) => {
+                let Some(monday) = week.parse().ok().and_then(|week| {
+                    NaiveDate::from_isoywd_opt(today.iso_week().year(), week, Weekday::Mon)
+                }) else {
+                    return Ok(None);
+                };
+
+                monday
+            }
+        };
+
+        let menus = CanteenMenus::find()
+            .select_only()
+            .column(canteen_data::Column::Date)
+            .column(canteen_menus::Column::Menu)
+            .join_rev(
+                JoinType::Join,
+                canteen_pivot_menus_data::Relation::Menu.def(),
+            )
+            .join(
+                JoinType::Join,
+                canteen_pivot_menus_data::Relation::Data.def(),
+            )
+            .filter(canteen_data::Column::Date.gte(monday))
+            .filter(canteen_data::Column::Date.lte(monday + chrono::Duration::days(6)))
+            .order_by(canteen_data::Column::Date, Order::Asc)
+            .order_by(canteen_menus::Column::Type, Order::Asc)
+            .into_tuple::<(Date, String)>()
+            .all(self.db)
+            .await
+            .map_err(db_error)?;
+
+        let mut days: BTreeMap<Date, Vec<String>> = BTreeMap::new();
+
+        for (date, menu) in menus {
+            days.entry(date).or_default().push(menu);
+        }
+
+        let mut html = String::from("<table class=\"shortcode-canteen\"><tbody>");
+
+        for (date, menus) in days {
+            write!(
+                html,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                date.format("%m. %d."),
+                menus
+                    .iter()
+                    .map(|menu| escape(menu))
+                    .collect::<Vec<_>>()
+                    .join("<br>"),
+            )
+            .unwrap();
+        }
+
+        html.push_str("</tbody></table>");
+
+        Ok(Some(html))
+    }
+}
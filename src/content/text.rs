@@ -0,0 +1,96 @@
+use super::{blocks::is_inline, render_markdown, strip_shortcodes};
+use crate::entity::sea_orm_active_enums::ContentFormat;
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+
+/// Average reading speed used for reading time estimates.
+const WORDS_PER_MINUTE: usize = 200;
+
+fn collect_text(node: NodeRef<Node>, text: &mut String) {
+    match node.value() {
+        Node::Text(t) => text.push_str(t),
+        Node::Element(element) => {
+            if matches!(element.name(), "script" | "style" | "template") {
+                return;
+            }
+
+            let separate = element.name() == "br" || !is_inline(element.name());
+
+            if separate {
+                text.push(' ');
+            }
+
+            for child in node.children() {
+                collect_text(child, text);
+            }
+
+            if separate {
+                text.push(' ');
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The readable text of stored content, with markup and shortcodes removed and
+/// whitespace collapsed.
+pub fn plain_text(content: &str, format: ContentFormat) -> String {
+    let html = match format {
+        ContentFormat::Html => strip_shortcodes(content),
+        ContentFormat::Markdown => strip_shortcodes(&render_markdown(content)),
+    };
+
+    let mut text = String::with_capacity(html.len());
+
+    for child in Html::parse_fragment(&html).root_element().children() {
+        collect_text(child, &mut text);
+    }
+
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Words of `text`: whitespace-separated runs containing at least one letter or
+/// digit, so hyphenated compounds (e.g. `Kossuth-díj`) count as one word and
+/// lone punctuation does not count at all.
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+}
+
+pub fn word_count(text: &str) -> usize {
+    words(text).count()
+}
+
+/// Estimated reading time in minutes, at least one.
+pub fn reading_time(words: usize) -> usize {
+    words.div_ceil(WORDS_PER_MINUTE).max(1)
+}
+
+/// The beginning of `text`, at most `length` characters long, cut at a word
+/// boundary and followed by an ellipsis if anything was left out.
+pub fn excerpt(text: &str, length: usize) -> String {
+    if text.chars().count() <= length {
+        return text.to_owned();
+    }
+
+    let mut excerpt = String::new();
+
+    for word in text.split(' ') {
+        let separator = usize::from(!excerpt.is_empty());
+
+        // Leave room for the ellipsis.
+        if excerpt.chars().count() + separator + word.chars().count() + 1 > length {
+            break;
+        }
+
+        if separator == 1 {
+            excerpt.push(' ');
+        }
+
+        excerpt.push_str(word);
+    }
+
+    let excerpt = excerpt.trim_end_matches(|c: char| !c.is_alphanumeric());
+
+    format!("{excerpt}…")
+}
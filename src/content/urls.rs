@@ -0,0 +1,121 @@
+use crate::Config;
+use lol_html::{RewriteStrSettings, element, rewrite_str};
+
+/// Whether `url` has a scheme (`https:`, `mailto:`, ...) or is protocol-relative.
+fn is_absolute(url: &str) -> bool {
+    url.starts_with("//")
+        || url
+            .split_once(':')
+            .is_some_and(|(scheme, _)| !scheme.is_empty() && !scheme.contains(['/', '?', '#']))
+}
+
+/// Rewrites asset URLs left over from the old site to point at the storage server,
+/// and decorates images and external links.
+pub struct UrlRewriter {
+    storage_base_url: String,
+    asset_paths: Vec<(String, String)>,
+    lazy_images: bool,
+    external_link_rel: bool,
+}
+
+impl UrlRewriter {
+    pub fn new(config: &Config) -> Self {
+        let asset_paths = config
+            .content_asset_paths
+            .split(',')
+            .filter_map(|entry| {
+                let (prefix, directory) = entry.trim().split_once('=')?;
+                Some((prefix.to_owned(), directory.to_owned()))
+            })
+            .collect();
+
+        Self {
+            storage_base_url: config.storage_base_url.clone(),
+            asset_paths,
+            lazy_images: config.content_lazy_images,
+            external_link_rel: config.content_external_link_rel,
+        }
+    }
+
+    /// Resolve a relative asset URL such as `/storage/posts_images/x.jpg` or
+    /// `../uploads/y.pdf` against the storage server.
+    ///
+    /// Returns `None` for absolute URLs and paths outside the configured asset directories.
+    pub fn resolve(&self, url: &str) -> Option<String> {
+        if is_absolute(url) || url.starts_with(['#', '?']) {
+            return None;
+        }
+
+        let mut path = url;
+
+        while let Some(rest) = path
+            .strip_prefix("../")
+            .or_else(|| path.strip_prefix("./"))
+            .or_else(|| path.strip_prefix('/'))
+        {
+            path = rest;
+        }
+
+        self.asset_paths.iter().find_map(|(prefix, directory)| {
+            let rest = path.strip_prefix(prefix.as_str())?;
+            Some(format!("{}/{directory}{rest}", self.storage_base_url))
+        })
+    }
+
+    pub fn rewrite(&self, html: &str) -> String {
+        let resolve_attribute = |attribute: &'static str| {
+            move |el: &mut lol_html::html_content::Element| {
+                if let Some(url) = el
+                    .get_attribute(attribute)
+                    .and_then(|url| self.resolve(&url))
+                {
+                    el.set_attribute(attribute, &url)?;
+                }
+
+                Ok(())
+            }
+        };
+
+        let mut element_content_handlers = vec![
+            element!("[src]", resolve_attribute("src")),
+            element!("a[href]", resolve_attribute("href")),
+        ];
+
+        if self.lazy_images {
+            element_content_handlers.push(element!("img:not([loading])", |el| {
+                el.set_attribute("loading", "lazy")?;
+                Ok(())
+            }));
+        }
+
+        if self.external_link_rel {
+            element_content_handlers.push(element!("a[href]", |el| {
+                if el
+                    .get_attribute("href")
+                    .is_some_and(|href| is_absolute(&href))
+                {
+                    let rel = el.get_attribute("rel").unwrap_or_default();
+
+                    if !rel.split_whitespace().any(|value| value == "noopener") {
+                        let rel = format!("{rel} noopener");
+                        el.set_attribute("rel", rel.trim_start())?;
+                    }
+                }
+
+                Ok(())
+            }));
+        }
+
+        rewrite_str(
+            html,
+            RewriteStrSettings {
+                element_content_handlers,
+                ..RewriteStrSettings::new()
+            },
+        )
+        .unwrap_or_else(|err| {
+            tracing::warn!("Could not rewrite content URLs: {err}");
+            html.to_owned()
+        })
+    }
+}
@@ -12,6 +12,9 @@ pub struct Model {
     pub title: String,
     pub description: Option<String>,
     pub color: Option<String>,
+    pub category_id: Option<i32>,
+    pub recurrence_rule: Option<String>,
+    pub uid: Option<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
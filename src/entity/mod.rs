@@ -2,14 +2,32 @@
 
 pub mod prelude;
 
+pub mod albums;
+pub mod albums_pivot_labels_data;
 pub mod canteen_data;
 pub mod canteen_menus;
 pub mod canteen_pivot_menus_data;
 pub mod colleagues_data;
+pub mod events_categories;
 pub mod events_data;
+pub mod events_exceptions;
+pub mod image_placeholders;
 pub mod menu_items;
+pub mod menu_items_translations;
 pub mod pages;
+pub mod pages_translations;
+pub mod posts_attachments;
 pub mod posts_authors;
 pub mod posts_data;
 pub mod posts_labels;
+pub mod posts_pins;
+pub mod posts_pivot_authors_data;
 pub mod posts_pivot_labels_data;
+pub mod posts_pivot_series_data;
+pub mod posts_revisions;
+pub mod posts_series;
+pub mod posts_translations;
+pub mod posts_views;
+pub mod school_calendar_entries;
+pub mod school_years;
+pub mod sea_orm_active_enums;
@@ -1,5 +1,6 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.4.2
 
+use super::sea_orm_active_enums::ContentFormat;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel)]
@@ -13,6 +14,7 @@ pub struct Model {
     pub slug: String,
     #[sea_orm(column_type = "Text")]
     pub content: String,
+    pub content_format: ContentFormat,
     pub extras: Json,
     pub created_at: DateTime,
     pub updated_at: DateTime,
@@ -1,5 +1,6 @@
 //! SeaORM Entity. Generated by sea-orm-codegen 0.4.2
 
+use super::sea_orm_active_enums::ContentFormat;
 use sea_orm::entity::prelude::*;
 
 #[derive(Clone, Debug, Eq, PartialEq, DeriveEntityModel)]
@@ -11,7 +12,9 @@ pub struct Model {
     pub color: String,
     pub description: Option<String>,
     pub content: Option<String>,
+    pub content_format: ContentFormat,
     pub index_image: Option<String>,
+    pub index_image_meta: Option<Json>,
     pub author_id: Option<i32>,
     pub images: Json,
     pub date: Option<DateTime>,
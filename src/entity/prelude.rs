@@ -2,14 +2,31 @@
 
 #![allow(unused_imports)]
 
+pub use super::albums::Entity as Albums;
+pub use super::albums_pivot_labels_data::Entity as AlbumsPivotLabelsData;
 pub use super::canteen_data::Entity as CanteenData;
 pub use super::canteen_menus::Entity as CanteenMenus;
 pub use super::canteen_pivot_menus_data::Entity as CanteenPivotMenusData;
 pub use super::colleagues_data::Entity as ColleaguesData;
+pub use super::events_categories::Entity as EventsCategories;
 pub use super::events_data::Entity as EventsData;
+pub use super::events_exceptions::Entity as EventsExceptions;
+pub use super::image_placeholders::Entity as ImagePlaceholders;
 pub use super::menu_items::Entity as MenuItems;
+pub use super::menu_items_translations::Entity as MenuItemsTranslations;
 pub use super::pages::Entity as Pages;
+pub use super::pages_translations::Entity as PagesTranslations;
+pub use super::posts_attachments::Entity as PostsAttachments;
 pub use super::posts_authors::Entity as PostsAuthors;
 pub use super::posts_data::Entity as PostsData;
 pub use super::posts_labels::Entity as PostsLabels;
+pub use super::posts_pins::Entity as PostsPins;
+pub use super::posts_pivot_authors_data::Entity as PostsPivotAuthorsData;
 pub use super::posts_pivot_labels_data::Entity as PostsPivotLabelsData;
+pub use super::posts_pivot_series_data::Entity as PostsPivotSeriesData;
+pub use super::posts_revisions::Entity as PostsRevisions;
+pub use super::posts_series::Entity as PostsSeries;
+pub use super::posts_translations::Entity as PostsTranslations;
+pub use super::posts_views::Entity as PostsViews;
+pub use super::school_calendar_entries::Entity as SchoolCalendarEntries;
+pub use super::school_years::Entity as SchoolYears;
@@ -0,0 +1,17 @@
+use async_graphql::{Context, Error, Guard, Result};
+
+/// Marker put into the request data when the request carries a valid editor token.
+pub struct Editor;
+
+/// Restricts a field to requests authenticated with the editor token.
+pub struct EditorGuard;
+
+impl Guard for EditorGuard {
+    async fn check(&self, ctx: &Context<'_>) -> Result<()> {
+        if ctx.data_opt::<Editor>().is_some() {
+            Ok(())
+        } else {
+            Err(Error::new("Forbidden"))
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use crate::utils::db_error;
+use async_graphql::{Context, Result};
+use sea_orm::{DatabaseTransaction, EntityTrait, Select};
+
+/// Locale of the content stored in the base tables.
+pub const DEFAULT_LOCALE: &str = "hu";
+
+/// A content locale, reduced to its primary language subtag (e.g. `en-GB` becomes `en`).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Locale(String);
+
+impl Locale {
+    pub fn new(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?;
+
+        if language.is_empty()
+            || language.len() > 8
+            || !language.chars().all(|c| c.is_ascii_alphabetic())
+        {
+            return None;
+        }
+
+        Some(Self(language.to_ascii_lowercase()))
+    }
+
+    /// Pick the preferred locale from an `Accept-Language` header value.
+    ///
+    /// Of several locales with the same quality, the first one is preferred.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        header
+            .split(',')
+            .filter_map(|item| {
+                let mut parts = item.split(';');
+                let locale = Self::new(parts.next()?)?;
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+
+                Some((locale, quality))
+            })
+            .filter(|(_, quality)| *quality > 0.0)
+            .fold(
+                None,
+                |best: Option<(Self, f32)>, (locale, quality)| match best {
+                    Some((_, best_quality)) if best_quality >= quality => best,
+                    _ => Some((locale, quality)),
+                },
+            )
+            .map(|(locale, _)| locale)
+    }
+
+    /// The locale requested by the `locale` argument, falling back to the
+    /// `Accept-Language` header of the request and then to the default locale.
+    pub fn resolve(ctx: &Context<'_>, argument: Option<String>) -> Self {
+        argument
+            .as_deref()
+            .and_then(Self::new)
+            .or_else(|| ctx.data_opt::<Locale>().cloned())
+            .unwrap_or_default()
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.0 == DEFAULT_LOCALE
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self(DEFAULT_LOCALE.to_owned())
+    }
+}
+
+/// Collect the default locale followed by the locales returned by `query`,
+/// which must select a single string column.
+pub async fn available_locales<E: EntityTrait>(
+    db: &DatabaseTransaction,
+    query: Select<E>,
+) -> Result<Vec<String>> {
+    let mut locales = vec![DEFAULT_LOCALE.to_owned()];
+
+    locales.extend(
+        query
+            .into_tuple::<String>()
+            .all(db)
+            .await
+            .map_err(db_error)?,
+    );
+
+    Ok(locales)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preferred(header: &str) -> Option<String> {
+        Locale::from_accept_language(header).map(|locale| locale.as_str().to_owned())
+    }
+
+    #[test]
+    fn reduces_tags_to_language() {
+        assert_eq!(Locale::new("en-GB").unwrap().as_str(), "en");
+        assert_eq!(Locale::new(" DE_at ").unwrap().as_str(), "de");
+        assert_eq!(Locale::new(""), None);
+        assert_eq!(Locale::new("*"), None);
+        assert_eq!(Locale::new("abcdefghi"), None);
+    }
+
+    #[test]
+    fn prefers_first_of_equal_quality() {
+        assert_eq!(preferred("en,hu").as_deref(), Some("en"));
+        assert_eq!(preferred("hu, en").as_deref(), Some("hu"));
+        assert_eq!(preferred("de;q=0.5, en;q=0.5").as_deref(), Some("de"));
+    }
+
+    #[test]
+    fn prefers_highest_quality() {
+        assert_eq!(preferred("hu;q=0.8, en").as_deref(), Some("en"));
+        assert_eq!(
+            preferred("de;q=0.2, en-US;q=0.9, hu;q=0.5").as_deref(),
+            Some("en")
+        );
+    }
+
+    #[test]
+    fn skips_rejected_and_invalid_entries() {
+        assert_eq!(preferred("en;q=0, hu;q=0.1").as_deref(), Some("hu"));
+        assert_eq!(preferred("en;q=abc, de").as_deref(), Some("de"));
+        assert_eq!(preferred("*, en;q=0.5").as_deref(), Some("en"));
+        assert_eq!(preferred("en;q=0"), None);
+        assert_eq!(preferred(""), None);
+    }
+}
@@ -1,41 +1,62 @@
 mod cache;
+mod guard;
+mod locale;
 pub mod resolvers;
 pub mod types;
 
-use crate::Config;
+use crate::{
+    Config,
+    content::{ContentCache, ContentRenderer},
+    views::ViewCounter,
+};
 use async_graphql::{
-    EmptyMutation, EmptySubscription, MergedObject,
+    EmptySubscription, MergedObject,
     extensions::{Analyzer, apollo_persisted_queries::ApolloPersistedQueries},
 };
 use cache::RedisCache;
 use resolvers::{
-    ArchiveQuery, AuthorsQuery, CanteenQuery, ColleaguesQuery, EventsQuery, LabelQuery, MenuQuery,
-    PagesQuery, PostsQuery,
+    AlbumsQuery, ArchiveQuery, AuthorsQuery, CanteenQuery, ColleaguesQuery, EventsQuery,
+    LabelQuery, MenuQuery, PagesQuery, PinsQuery, PostsQuery, RevisionsMutation, SchoolQuery,
+    SeriesQuery, UploadsMutation, ViewsMutation, ViewsQuery,
 };
 
+pub use guard::*;
+pub use locale::*;
+
 #[derive(MergedObject, Default)]
 pub struct Query(
     CanteenQuery,
     ColleaguesQuery,
     EventsQuery,
+    SchoolQuery,
     PagesQuery,
     AuthorsQuery,
     PostsQuery,
     LabelQuery,
     MenuQuery,
     ArchiveQuery,
+    AlbumsQuery,
+    SeriesQuery,
+    PinsQuery,
+    ViewsQuery,
 );
 
-pub type Schema = async_graphql::Schema<Query, EmptyMutation, EmptySubscription>;
+#[derive(MergedObject, Default)]
+pub struct Mutation(RevisionsMutation, UploadsMutation, ViewsMutation);
+
+pub type Schema = async_graphql::Schema<Query, Mutation, EmptySubscription>;
 
-pub async fn create_schema(config: &Config) -> Schema {
+pub async fn create_schema(config: &Config, views: ViewCounter) -> Schema {
     let cache = RedisCache::new(&config.redis_url)
         .await
         .expect("Could not create redis cache");
 
-    let schema = Schema::build(Query::default(), EmptyMutation, EmptySubscription)
+    let schema = Schema::build(Query::default(), Mutation::default(), EmptySubscription)
         .extension(Analyzer)
         .extension(ApolloPersistedQueries::new(cache))
+        .data(ContentRenderer::new(config))
+        .data(ContentCache::new(config))
+        .data(views)
         .limit_complexity(256)
         .finish();
 
@@ -0,0 +1,472 @@
+use super::{Image, Post};
+use crate::{
+    Config,
+    entity::{
+        albums::{self, Entity as Albums},
+        albums_pivot_labels_data,
+        posts_data::{self, Entity as PostsData},
+        posts_pivot_labels_data,
+        posts_translations::{self, Entity as PostsTranslations},
+    },
+    graphql::{
+        Locale,
+        types::{AlbumCursor, Date},
+    },
+    media::ImageMeta,
+    utils::db_error,
+};
+use async_graphql::{
+    ComplexObject, Context, Enum, Error, Object, Result, SimpleObject,
+    connection::{Connection, Edge, EmptyFields, query},
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    Condition, DatabaseTransaction, FromQueryResult, JoinType,
+    prelude::*,
+    query::{Order, QueryOrder, QuerySelect},
+    sea_query::Expr,
+};
+use serde_json::Value as Json;
+use std::{cmp::Ordering, ops::Deref, sync::Arc};
+
+/// Number of albums returned when neither `first` nor `last` is given.
+const DEFAULT_PAGE_SIZE: usize = 20;
+
+/// Where the images of an album come from.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlbumKind {
+    /// The gallery of a post.
+    Post,
+    /// An album not tied to a post.
+    Standalone,
+}
+
+impl AlbumKind {
+    fn image_kind(self) -> &'static str {
+        match self {
+            Self::Post => "posts",
+            Self::Standalone => "albums",
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct AlbumRow {
+    id: u32,
+    title: String,
+    description: Option<String>,
+    date: NaiveDateTime,
+    cover: Option<String>,
+    cover_meta: Option<Json>,
+    images: Json,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct AlbumTranslation {
+    posts_id: u32,
+    title: String,
+    description: Option<String>,
+}
+
+/// A photo album: the gallery of a post, or a standalone album.
+#[derive(SimpleObject, Debug)]
+#[graphql(complex)]
+pub struct Album {
+    /// Whether the album is the gallery of a post or stands alone.
+    pub kind: AlbumKind,
+    /// ID of the post for post albums, or of the album itself for standalone ones.
+    pub id: u32,
+    /// Album title.
+    pub title: String,
+    /// Short description.
+    pub description: Option<String>,
+    /// Date of the album.
+    pub date: Date,
+    /// Cover image, falling back to the first image of the album.
+    pub cover: Option<Image>,
+    /// Images of the album.
+    pub images: Vec<Image>,
+    #[graphql(skip)]
+    pub cursor: AlbumCursor,
+}
+
+impl Album {
+    fn new(config: &Config, kind: AlbumKind, row: AlbumRow) -> Result<Self> {
+        let image_kind = kind.image_kind();
+        let images = Image::gallery(config, image_kind, &row.images)
+            .ok_or_else(|| Error::new("invalid data in database"))?;
+        let cover = row
+            .cover
+            .filter(|cover| !cover.is_empty())
+            .map(|cover| {
+                Image::new(
+                    config,
+                    image_kind,
+                    &cover,
+                    ImageMeta::from_json(row.cover_meta.as_ref()),
+                )
+            })
+            .or_else(|| images.first().cloned());
+
+        Ok(Self {
+            kind,
+            id: row.id,
+            title: row.title,
+            description: row.description,
+            date: Date(row.date.date()),
+            cover,
+            images,
+            cursor: AlbumCursor::new(row.date, kind, row.id),
+        })
+    }
+}
+
+#[ComplexObject]
+impl Album {
+    /// The post of a post album.
+    async fn post(&self, ctx: &Context<'_>) -> Result<Option<Post>> {
+        if self.kind != AlbumKind::Post {
+            return Ok(None);
+        }
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::Id);
+
+        query = Post::columns(ctx, query);
+
+        let mut post = query
+            .filter(posts_data::Column::Id.eq(self.id))
+            .filter(posts_data::Column::Published.eq(true))
+            .into_model::<Post>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        Post::translate(post.as_mut_slice(), db, &Locale::resolve(ctx, None)).await?;
+
+        Ok(post)
+    }
+}
+
+/// Condition selecting rows of `kind` ordered before (`less`) or after `cursor`.
+fn cursor_condition<C: ColumnTrait>(
+    date: C,
+    id: C,
+    kind: AlbumKind,
+    cursor: &AlbumCursor,
+    less: bool,
+) -> Condition {
+    let (date_strict, date_or_equal, id_strict) = if less {
+        (
+            date.lt(cursor.date()),
+            date.lte(cursor.date()),
+            id.lt(cursor.id()),
+        )
+    } else {
+        (
+            date.gt(cursor.date()),
+            date.gte(cursor.date()),
+            id.gt(cursor.id()),
+        )
+    };
+
+    // Albums are ordered by date, then by kind, then by ID.
+    match (kind.cmp(&cursor.kind()), less) {
+        (Ordering::Equal, _) => Condition::any()
+            .add(date_strict)
+            .add(Condition::all().add(date.eq(cursor.date())).add(id_strict)),
+        (Ordering::Less, true) | (Ordering::Greater, false) => Condition::all().add(date_or_equal),
+        (Ordering::Less, false) | (Ordering::Greater, true) => Condition::all().add(date_strict),
+    }
+}
+
+/// Condition shared by both album kinds: the year and the pagination cursors.
+fn album_condition<C: ColumnTrait>(
+    date: C,
+    id: C,
+    kind: AlbumKind,
+    year: Option<i32>,
+    after: Option<&AlbumCursor>,
+    before: Option<&AlbumCursor>,
+) -> Result<Condition> {
+    let mut condition = Condition::all();
+
+    if let Some(year) = year {
+        let start = NaiveDate::from_ymd_opt(year, 1, 1)
+            .ok_or_else(|| Error::new("invalid year"))?
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let end = NaiveDate::from_ymd_opt(year + 1, 1, 1)
+            .ok_or_else(|| Error::new("invalid year"))?
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+
+        condition = condition.add(date.gte(start)).add(date.lt(end));
+    }
+
+    if let Some(after) = after {
+        condition = condition.add(cursor_condition(date, id, kind, after, true));
+    }
+
+    if let Some(before) = before {
+        condition = condition.add(cursor_condition(date, id, kind, before, false));
+    }
+
+    Ok(condition)
+}
+
+fn post_albums(condition: Condition, label: Option<u32>) -> Select<PostsData> {
+    let mut query = PostsData::find()
+        .select_only()
+        .column(posts_data::Column::Id)
+        .column(posts_data::Column::Title)
+        .column(posts_data::Column::Description)
+        // `posts_data.date` is a DATE, decoded like the DATETIME of standalone albums.
+        .column_as(Expr::cust("CAST(`posts_data`.`date` AS DATETIME)"), "date")
+        .column_as(posts_data::Column::IndexImage, "cover")
+        .column_as(posts_data::Column::IndexImageMeta, "cover_meta")
+        .column(posts_data::Column::Images)
+        .filter(posts_data::Column::Published.eq(true))
+        .filter(posts_data::Column::Date.is_not_null())
+        .filter(Expr::cust("JSON_LENGTH(`posts_data`.`images`) > 0"))
+        .filter(condition);
+
+    if let Some(label) = label {
+        query = query
+            .join_rev(
+                JoinType::Join,
+                posts_pivot_labels_data::Relation::Posts.def(),
+            )
+            .filter(posts_pivot_labels_data::Column::LabelsId.eq(label));
+    }
+
+    query
+}
+
+fn standalone_albums(condition: Condition, label: Option<u32>) -> Select<Albums> {
+    let mut query = Albums::find()
+        .select_only()
+        .column(albums::Column::Id)
+        .column(albums::Column::Title)
+        .column(albums::Column::Description)
+        .column(albums::Column::Date)
+        .column(albums::Column::Cover)
+        .column(albums::Column::CoverMeta)
+        .column(albums::Column::Images)
+        .filter(albums::Column::Published.eq(true))
+        .filter(condition);
+
+    if let Some(label) = label {
+        query = query
+            .join_rev(
+                JoinType::Join,
+                albums_pivot_labels_data::Relation::Albums.def(),
+            )
+            .filter(albums_pivot_labels_data::Column::LabelsId.eq(label));
+    }
+
+    query
+}
+
+/// Replace the titles and descriptions of post albums with their translation to `locale`.
+async fn translate(rows: &mut [AlbumRow], db: &DatabaseTransaction, locale: &Locale) -> Result<()> {
+    if locale.is_default() || rows.is_empty() {
+        return Ok(());
+    }
+
+    let translations = PostsTranslations::find()
+        .select_only()
+        .column(posts_translations::Column::PostsId)
+        .column(posts_translations::Column::Title)
+        .column(posts_translations::Column::Description)
+        .filter(posts_translations::Column::PostsId.is_in(rows.iter().map(|row| row.id)))
+        .filter(posts_translations::Column::Locale.eq(locale.as_str()))
+        .into_model::<AlbumTranslation>()
+        .all(db)
+        .await
+        .map_err(db_error)?;
+
+    for row in rows {
+        let Some(translation) = translations
+            .iter()
+            .find(|translation| translation.posts_id == row.id)
+        else {
+            continue;
+        };
+
+        row.title = translation.title.clone();
+
+        if translation.description.is_some() {
+            row.description = translation.description.clone();
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+pub struct AlbumsQuery;
+
+#[Object]
+impl AlbumsQuery {
+    /// Retrieve a paginated list of photo albums, newest first.
+    ///
+    /// Every published post with images is an album, alongside standalone albums.
+    #[allow(clippy::too_many_arguments)]
+    async fn albums(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Only albums with this label.")] label: Option<u32>,
+        #[graphql(desc = "Only albums from this year.")] year: Option<i32>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<AlbumCursor, Album, EmptyFields, EmptyFields>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "albums"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let config = ctx.data_unchecked::<Config>();
+        let locale = Locale::resolve(ctx, None);
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<AlbumCursor>, before: Option<AlbumCursor>, first, last| async move {
+                // Paginating backwards reads the albums in ascending order, then reverses them.
+                let backwards = first.is_none() && last.is_some();
+                let limit = first.or(last).unwrap_or(DEFAULT_PAGE_SIZE);
+                let order = if backwards { Order::Asc } else { Order::Desc };
+
+                let mut posts = post_albums(
+                    album_condition(
+                        posts_data::Column::Date,
+                        posts_data::Column::Id,
+                        AlbumKind::Post,
+                        year,
+                        after.as_ref(),
+                        before.as_ref(),
+                    )?,
+                    label,
+                )
+                .order_by(posts_data::Column::Date, order.clone())
+                .order_by(posts_data::Column::Id, order.clone())
+                .limit(limit as u64 + 1)
+                .into_model::<AlbumRow>()
+                .all(db.deref())
+                .await
+                .map_err(db_error)?;
+
+                let standalone = standalone_albums(
+                    album_condition(
+                        albums::Column::Date,
+                        albums::Column::Id,
+                        AlbumKind::Standalone,
+                        year,
+                        after.as_ref(),
+                        before.as_ref(),
+                    )?,
+                    label,
+                )
+                .order_by(albums::Column::Date, order.clone())
+                .order_by(albums::Column::Id, order)
+                .limit(limit as u64 + 1)
+                .into_model::<AlbumRow>()
+                .all(db.deref())
+                .await
+                .map_err(db_error)?;
+
+                translate(&mut posts, db, &locale).await?;
+
+                let mut albums = posts
+                    .into_iter()
+                    .map(|row| Album::new(config, AlbumKind::Post, row))
+                    .chain(
+                        standalone
+                            .into_iter()
+                            .map(|row| Album::new(config, AlbumKind::Standalone, row)),
+                    )
+                    .collect::<Result<Vec<_>>>()?;
+
+                albums.sort_by_key(|album| std::cmp::Reverse(album.cursor));
+
+                if backwards {
+                    albums.reverse();
+                }
+
+                let has_more = albums.len() > limit;
+
+                albums.truncate(limit);
+
+                let mut connection = if backwards {
+                    albums.reverse();
+                    Connection::new(has_more, before.is_some())
+                } else {
+                    Connection::new(after.is_some(), has_more)
+                };
+
+                connection.edges.extend(
+                    albums
+                        .into_iter()
+                        .map(|album| Edge::new(album.cursor, album)),
+                );
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+
+    /// Retrieve a single album by its kind and ID.
+    async fn album(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Whether the album is the gallery of a post or stands alone.")]
+        kind: AlbumKind,
+        #[graphql(desc = "The post ID for post albums, or the album ID.")] id: u32,
+    ) -> Result<Option<Album>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "album"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let row = match kind {
+            AlbumKind::Post => {
+                post_albums(Condition::all().add(posts_data::Column::Id.eq(id)), None)
+                    .into_model::<AlbumRow>()
+                    .one(db.deref())
+                    .await
+            }
+            AlbumKind::Standalone => {
+                standalone_albums(Condition::all().add(albums::Column::Id.eq(id)), None)
+                    .into_model::<AlbumRow>()
+                    .one(db.deref())
+                    .await
+            }
+        }
+        .map_err(db_error)?;
+
+        let Some(mut row) = row else {
+            return Ok(None);
+        };
+
+        if kind == AlbumKind::Post {
+            translate(
+                std::slice::from_mut(&mut row),
+                db,
+                &Locale::resolve(ctx, None),
+            )
+            .await?;
+        }
+
+        Album::new(ctx.data_unchecked::<Config>(), kind, row).map(Some)
+    }
+}
@@ -1,7 +1,7 @@
 use super::Post;
 use crate::{
     entity::posts_data::{Column, Entity as PostsData},
-    select_columns,
+    graphql::Locale,
     utils::db_error,
 };
 use async_graphql::{Context, Error, Object, Result, SimpleObject};
@@ -40,12 +40,9 @@ impl Archive {
         #[graphql(desc = "The month (1-12).")] month: u32,
     ) -> Result<Vec<Post>> {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = PostsData::find().select_only();
+        let mut query = PostsData::find().select_only().column(Column::Id);
 
-        select_columns!(ctx, query, Column);
-        select_columns!(ctx, query,
-            "author" => Column::AuthorId,
-            "labels" => Column::Id);
+        query = Post::columns(ctx, query);
 
         let start = NaiveDate::from_ymd_opt(year, month, 1)
             .ok_or_else(|| Error::new("invalid date"))?
@@ -61,7 +58,7 @@ impl Archive {
         .and_hms_opt(0, 0, 0)
         .unwrap();
 
-        query
+        let mut posts = query
             .filter(Column::Date.gte(start))
             .filter(Column::Date.lt(end))
             .filter(Column::Published.eq(true))
@@ -69,7 +66,11 @@ impl Archive {
             .into_model::<Post>()
             .all(db.deref())
             .await
-            .map_err(db_error)
+            .map_err(db_error)?;
+
+        Post::translate(&mut posts, db, &Locale::resolve(ctx, None)).await?;
+
+        Ok(posts)
     }
 
     /// Get a summary of post counts grouped by year and month.
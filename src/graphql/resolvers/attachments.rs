@@ -0,0 +1,41 @@
+use crate::{Config, media::ATTACHMENTS_DIRECTORY};
+use async_graphql::{ComplexObject, Context, SimpleObject};
+use sea_orm::FromQueryResult;
+
+/// A downloadable file attached to a post, e.g. a form to fill in.
+#[derive(SimpleObject, Debug, FromQueryResult)]
+#[graphql(complex)]
+pub struct Attachment {
+    /// Unique identifier.
+    pub id: u32,
+    /// File name shown to readers.
+    pub name: String,
+    #[graphql(skip)]
+    pub file: String,
+    /// Size in bytes.
+    pub size: u64,
+    /// MIME type, e.g. `application/pdf`.
+    pub mime_type: String,
+    /// Number of downloads through `downloadUrl`.
+    pub download_count: u32,
+}
+
+#[ComplexObject]
+impl Attachment {
+    /// Direct URL of the file on the storage server.
+    async fn url(&self, ctx: &Context<'_>) -> String {
+        let config = ctx.data_unchecked::<Config>();
+
+        format!(
+            "{}/{ATTACHMENTS_DIRECTORY}/{}",
+            config.storage_base_url, self.file
+        )
+    }
+
+    /// URL that downloads the file under its `name` and counts the download.
+    async fn download_url(&self, ctx: &Context<'_>) -> String {
+        let config = ctx.data_unchecked::<Config>();
+
+        format!("{}/download/{}", config.public_base_url, self.id)
+    }
+}
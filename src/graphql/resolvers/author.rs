@@ -1,11 +1,12 @@
-use super::Post;
+use super::{ImagePlaceholder, Post, currently_pinned};
 use crate::{
     Config,
     entity::{
         posts_authors::{self, Entity as PostsAuthors},
-        posts_data,
+        posts_pivot_authors_data,
     },
     graphql::types::PostCursor,
+    media::{VariantFormat, srcset},
     select_columns,
     utils::{Maybe, create_paginated_posts, db_error},
 };
@@ -51,13 +52,53 @@ impl Author {
         )))
     }
 
-    /// Paginated list of posts written by this author.
+    /// Resized variants of the profile image for the `srcset` attribute, or null if
+    /// there is no image or resizing is not available.
+    async fn image_srcset(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Widths to include. Defaults to all allowed widths.")] widths: Option<
+            Vec<u32>,
+        >,
+        #[graphql(desc = "Output format. Defaults to the original format.")] format: Option<
+            VariantFormat,
+        >,
+    ) -> Result<Option<String>> {
+        let Some(ref image) = *self.image else {
+            return Err(Error::new("Database error: image not selected"));
+        };
+
+        Ok(image.as_deref().and_then(|image| {
+            srcset(
+                ctx.data_unchecked::<Config>(),
+                "authors",
+                image,
+                widths.as_deref(),
+                format,
+            )
+        }))
+    }
+
+    /// Placeholder to show while the profile image is loading, or null if there is no
+    /// image or its placeholder is not computed yet.
+    async fn image_placeholder(&self, ctx: &Context<'_>) -> Result<Option<ImagePlaceholder>> {
+        let Some(ref image) = *self.image else {
+            return Err(Error::new("Database error: image not selected"));
+        };
+
+        match image {
+            Some(image) => ImagePlaceholder::find(ctx, "authors", image).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Paginated list of posts written or co-written by this author.
     ///
-    /// Use `featured: true` to filter only featured posts.
+    /// Use `featured: true` to filter only posts that are currently pinned.
     async fn posts(
         &self,
         ctx: &Context<'_>,
-        #[graphql(default = false, desc = "Filter to only featured posts.")] featured: bool,
+        #[graphql(default = false, desc = "Filter to only currently pinned posts.")] featured: bool,
         after: Option<String>,
         before: Option<String>,
         first: Option<i32>,
@@ -66,17 +107,27 @@ impl Author {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
         let condition = {
             let condition = if featured {
-                Some(posts_data::Column::Featured.eq(true))
+                Some(currently_pinned())
             } else {
                 None
             };
 
             Condition::all()
                 .add_option(condition)
-                .add(posts_data::Column::AuthorId.eq(self.id.unwrap()))
+                .add(posts_pivot_authors_data::Column::AuthorsId.eq(self.id.unwrap()))
         };
 
-        create_paginated_posts(after, before, first, last, ctx, db, condition, None).await
+        create_paginated_posts(
+            after,
+            before,
+            first,
+            last,
+            ctx,
+            db,
+            condition,
+            Some(posts_pivot_authors_data::Relation::Posts.def()),
+        )
+        .await
     }
 }
 
@@ -99,7 +150,9 @@ impl AuthorsQuery {
         let mut query = PostsAuthors::find().select_only();
 
         select_columns!(ctx, query, posts_authors::Column);
-        select_columns!(ctx, query, "posts" => posts_authors::Column::Id);
+        select_columns!(ctx, query,
+            "posts" => posts_authors::Column::Id,
+            "imagePlaceholder" | "imageSrcset" => posts_authors::Column::Image);
 
         query
             .filter(posts_authors::Column::Id.eq(id))
@@ -1,3 +1,4 @@
+use super::SchoolDay;
 use crate::select_columns;
 use crate::utils::Maybe;
 use crate::{
@@ -61,6 +62,13 @@ impl Canteen {
             .await
             .map_err(db_error)
     }
+
+    /// Whether there is school on this day, and why not.
+    async fn school_day(&self, ctx: &Context<'_>) -> Result<SchoolDay> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        SchoolDay::find(db, self.date.as_ref().unwrap().0).await
+    }
 }
 
 #[derive(Default)]
@@ -86,6 +94,7 @@ impl CanteenQuery {
 
         select_columns!(ctx, query, canteen_data::Column);
         select_columns!(ctx, query, "menus" => canteen_data::Column::Id);
+        select_columns!(ctx, query, "schoolDay" => canteen_data::Column::Date);
 
         let start = NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Mon).unwrap();
         let end = NaiveDate::from_isoywd_opt(year, week as u32, Weekday::Sun).unwrap();
@@ -1,6 +1,8 @@
+use super::ImagePlaceholder;
 use crate::{
     Config,
     entity::colleagues_data::{Column, Entity as ColleaguesData},
+    media::{VariantFormat, srcset},
     select_columns,
     utils::{Maybe, db_error},
 };
@@ -50,6 +52,41 @@ impl Colleague {
             config.storage_base_url, image
         )))
     }
+
+    /// Resized variants of the profile image for the `srcset` attribute, or null if
+    /// there is no image or resizing is not available.
+    async fn image_srcset(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Widths to include. Defaults to all allowed widths.")] widths: Option<
+            Vec<u32>,
+        >,
+        #[graphql(desc = "Output format. Defaults to the original format.")] format: Option<
+            VariantFormat,
+        >,
+    ) -> Option<String> {
+        let Some(Some(ref image)) = *self.image else {
+            return None;
+        };
+
+        srcset(
+            ctx.data_unchecked::<Config>(),
+            "colleagues",
+            image,
+            widths.as_deref(),
+            format,
+        )
+    }
+
+    /// Placeholder to show while the profile image is loading, or null if there is no
+    /// image or its placeholder is not computed yet.
+    async fn image_placeholder(&self, ctx: &Context<'_>) -> Result<Option<ImagePlaceholder>> {
+        let Some(Some(ref image)) = *self.image else {
+            return Ok(None);
+        };
+
+        ImagePlaceholder::find(ctx, "colleagues", image).await
+    }
 }
 
 #[derive(Default)]
@@ -69,6 +106,7 @@ impl ColleaguesQuery {
         let mut query = ColleaguesData::find().select_only();
 
         select_columns!(ctx, query, Column);
+        select_columns!(ctx, query, "imagePlaceholder" | "imageSrcset" => Column::Image);
 
         let mut res = query
             .order_by_asc(Column::Name)
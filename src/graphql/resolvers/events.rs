@@ -1,21 +1,56 @@
+use super::SchoolDay;
 use crate::{
-    entity::events_data::{Column, Entity as EventsData},
-    graphql::types::DateTime,
-    select_columns,
+    calendar::{
+        Instance, MAX_OCCURRENCES, MonthGrid, RecurrenceRule, SchoolCalendar, SchoolDayKind,
+        Series, event_days, find_exceptions, in_categories, is_weekend, layout_week, overlapping,
+        visible,
+    },
+    entity::{
+        events_categories::{self, Entity as EventsCategories},
+        events_data::{Column, Entity as EventsData},
+        events_exceptions,
+    },
+    graphql::{
+        Editor,
+        types::{Date, DateTime, EventCursor},
+    },
+    select_columns, select_columns_connection,
     utils::{Maybe, db_error},
 };
-use async_graphql::{Context, Error, Object, Result, SimpleObject};
-use chrono::{Datelike, Duration, NaiveDate};
+use async_graphql::{
+    ComplexObject, Context, Error, Object, Result, SimpleObject,
+    connection::{Connection, Edge, EmptyFields, query},
+};
+use chrono::{Datelike, Local, NaiveDateTime};
 use prometheus::{IntCounterVec, labels};
 use sea_orm::{
-    DatabaseTransaction, FromQueryResult,
+    Condition, DatabaseTransaction, FromQueryResult, Select,
     prelude::*,
     query::{Order, QueryOrder, QuerySelect},
 };
-use std::{ops::Deref, sync::Arc};
+use std::{collections::HashMap, ops::Deref, sync::Arc};
 
-/// A calendar event.
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_UPCOMING_EVENTS: u32 = 50;
+
+/// A category of calendar events, shown in the legend of the calendar.
 #[derive(SimpleObject, Debug, FromQueryResult)]
+pub struct EventCategory {
+    /// Unique identifier.
+    id: Maybe<u32>,
+    /// Category name.
+    name: Maybe<String>,
+    /// Display color of the events in the category.
+    color: Maybe<String>,
+    /// Name of the icon shown for the category.
+    icon: Maybe<Option<String>>,
+    /// Whether the events in the category are shown to everyone, not only to editors.
+    public: Maybe<bool>,
+}
+
+/// A calendar event.
+#[derive(SimpleObject, Clone, Debug, FromQueryResult)]
+#[graphql(complex)]
 pub struct Event {
     /// Unique identifier.
     id: Maybe<u32>,
@@ -29,6 +64,380 @@ pub struct Event {
     description: Maybe<Option<String>>,
     /// Display color for the event.
     color: Maybe<Option<String>>,
+    #[graphql(skip)]
+    category_id: Maybe<Option<u32>>,
+    /// Recurrence rule of the event in the RFC 5545 `RRULE` format, e.g.
+    /// `FREQ=WEEKLY;BYDAY=MO`, if it recurs.
+    recurrence_rule: Maybe<Option<String>>,
+    /// Start of this occurrence according to the recurrence rule, identifying it among the
+    /// occurrences of a recurring event. It differs from `dateFrom` if the occurrence was
+    /// moved.
+    #[sea_orm(skip)]
+    occurrence: Option<DateTime>,
+}
+
+impl Event {
+    pub fn columns(ctx: &Context<'_>, mut query: Select<EventsData>) -> Select<EventsData> {
+        select_columns!(ctx, query, Column);
+        select_columns!(ctx, query,
+            "category" => Column::CategoryId,
+            "happeningNow" => Column::DateFrom,
+            "happeningNow" => Column::DateTo);
+
+        query
+    }
+
+    pub fn connection_columns(
+        ctx: &Context<'_>,
+        mut query: Select<EventsData>,
+    ) -> Select<EventsData> {
+        select_columns_connection!(ctx, query, Column);
+        select_columns_connection!(ctx, query,
+            "category" => Column::CategoryId,
+            "happeningNow" => Column::DateFrom,
+            "happeningNow" => Column::DateTo);
+
+        query
+    }
+
+    fn cursor(&self) -> EventCursor {
+        EventCursor::new(self.date_from.unwrap().0, self.id.unwrap())
+    }
+
+    /// Copy of a recurring event for one of its occurrences.
+    fn with_instance(&self, instance: Instance) -> Self {
+        Self {
+            id: self.id,
+            date_from: Maybe(Some(DateTime(instance.date_from))),
+            date_to: Maybe(Some(DateTime(instance.date_to))),
+            title: instance
+                .title
+                .map_or_else(|| self.title.clone(), |title| Maybe(Some(title))),
+            description: instance.description.map_or_else(
+                || self.description.clone(),
+                |description| Maybe(Some(Some(description))),
+            ),
+            color: self.color.clone(),
+            category_id: self.category_id,
+            recurrence_rule: self.recurrence_rule.clone(),
+            occurrence: Some(DateTime(instance.occurrence)),
+        }
+    }
+}
+
+#[ComplexObject]
+impl Event {
+    /// The category of the event.
+    async fn category(&self, ctx: &Context<'_>) -> Result<Option<EventCategory>> {
+        let Some(category_id) = self.category_id.flatten() else {
+            return Ok(None);
+        };
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = EventsCategories::find().select_only();
+
+        select_columns!(ctx, query, events_categories::Column);
+
+        query
+            .filter(events_categories::Column::Id.eq(category_id))
+            .into_model::<EventCategory>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)
+    }
+
+    /// Whether the event has already started and has not ended yet.
+    async fn happening_now(&self) -> Option<bool> {
+        let now = Local::now().naive_local();
+
+        Some(self.date_from.as_ref()?.0 <= now && now < self.date_to.as_ref()?.0)
+    }
+}
+
+/// Events the request may see, in one of `categories` if given.
+fn event_filter(ctx: &Context<'_>, categories: Option<&[u32]>) -> Condition {
+    Condition::all()
+        .add(visible(ctx.data_opt::<Editor>().is_some()))
+        .add(in_categories(categories))
+}
+
+/// Condition selecting events ordered before (`less`) or after `cursor`.
+fn cursor_condition(cursor: &EventCursor, less: bool) -> Condition {
+    let (date_strict, id_strict) = if less {
+        (
+            Column::DateFrom.lt(cursor.date()),
+            Column::Id.lt(cursor.id()),
+        )
+    } else {
+        (
+            Column::DateFrom.gt(cursor.date()),
+            Column::Id.gt(cursor.id()),
+        )
+    };
+
+    Condition::any().add(date_strict).add(
+        Condition::all()
+            .add(Column::DateFrom.eq(cursor.date()))
+            .add(id_strict),
+    )
+}
+
+/// Which part of a list of events to return.
+#[derive(Default)]
+struct Page {
+    after: Option<EventCursor>,
+    before: Option<EventCursor>,
+    limit: Option<usize>,
+    /// Whether the page is counted from the end of the list.
+    backwards: bool,
+}
+
+impl Page {
+    fn contains(&self, event: &Event) -> bool {
+        let cursor = event.cursor();
+
+        self.after.is_none_or(|after| cursor > after)
+            && self.before.is_none_or(|before| cursor < before)
+    }
+
+    /// Number of events to find: one more than the limit, to tell if there are more.
+    fn fetch_limit(&self) -> Option<usize> {
+        self.limit.map(|limit| limit + 1)
+    }
+}
+
+/// The events selected by `query` between `start` and `end`, with recurring events expanded
+/// into their occurrences, ordered by start and ID, or in reverse if the page is backwards.
+///
+/// With a limit, one more event than the limit is returned to tell if there are more.
+async fn find_events(
+    db: &DatabaseTransaction,
+    query: Select<EventsData>,
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+    page: &Page,
+) -> Result<Vec<Event>> {
+    let query = query
+        .column(Column::Id)
+        .column(Column::DateFrom)
+        .column(Column::DateTo)
+        .column(Column::RecurrenceRule);
+
+    let order = if page.backwards {
+        Order::Desc
+    } else {
+        Order::Asc
+    };
+    let limit = page.fetch_limit();
+
+    let events = query
+        .clone()
+        .filter(overlapping(start, end))
+        .filter(Column::RecurrenceRule.is_null())
+        .filter(
+            Condition::all()
+                .add_option(page.after.map(|after| cursor_condition(&after, false)))
+                .add_option(page.before.map(|before| cursor_condition(&before, true))),
+        )
+        .order_by(Column::DateFrom, order.clone())
+        .order_by(Column::Id, order)
+        .limit(limit.map(|limit| limit as u64))
+        .into_model::<Event>()
+        .all(db)
+        .await
+        .map_err(db_error)?;
+
+    let recurring = query
+        .filter(Column::RecurrenceRule.is_not_null())
+        .filter(Condition::all().add_option(end.map(|end| Column::DateFrom.lt(end))))
+        .into_model::<Event>()
+        .all(db)
+        .await
+        .map_err(db_error)?;
+
+    let exceptions = find_exceptions(db, recurring.iter().map(|event| event.id.unwrap() as i32))
+        .await
+        .map_err(db_error)?;
+
+    Ok(add_occurrences(
+        events, recurring, exceptions, start, end, page,
+    ))
+}
+
+/// Add the occurrences of the `recurring` events on `page` to the single `events` found on
+/// it, then order and limit them as [`find_events`] returns them.
+fn add_occurrences(
+    mut events: Vec<Event>,
+    recurring: Vec<Event>,
+    mut exceptions: HashMap<i32, Vec<events_exceptions::Model>>,
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+    page: &Page,
+) -> Vec<Event> {
+    let limit = page.fetch_limit();
+
+    // Going forward, the first occurrences of each event are enough to fill the page.
+    let occurrences = match limit {
+        Some(limit) if !page.backwards => limit,
+        _ => MAX_OCCURRENCES,
+    };
+
+    for event in recurring {
+        let id = event.id.unwrap();
+        let rule = event.recurrence_rule.as_ref().and_then(Option::as_deref);
+
+        match rule.map(str::parse::<RecurrenceRule>) {
+            Some(Ok(rule)) => {
+                let series = Series::new(
+                    rule,
+                    event.date_from.unwrap().0,
+                    event.date_to.unwrap().0,
+                    exceptions.remove(&(id as i32)).unwrap_or_default(),
+                );
+
+                events.extend(
+                    series
+                        .instances(start, end)
+                        .map(|instance| event.with_instance(instance))
+                        .filter(|occurrence| page.contains(occurrence))
+                        .take(occurrences),
+                );
+            }
+            Some(Err(err)) => {
+                tracing::warn!("Invalid recurrence rule of event {id}: {err}");
+
+                if event.date_to.unwrap().0 >= start && page.contains(&event) {
+                    events.push(event);
+                }
+            }
+            None => {}
+        }
+    }
+
+    events.sort_by_key(|event| event.cursor());
+
+    if page.backwards {
+        events.reverse();
+    }
+
+    if let Some(limit) = limit {
+        events.truncate(limit);
+    }
+
+    events
+}
+
+/// Page through the events selected by `query` between `start` and `end`, ordered by start
+/// and ID.
+#[allow(clippy::too_many_arguments)]
+async fn paginate_events(
+    db: &DatabaseTransaction,
+    select: Select<EventsData>,
+    start: NaiveDateTime,
+    end: Option<NaiveDateTime>,
+    after: Option<String>,
+    before: Option<String>,
+    first: Option<i32>,
+    last: Option<i32>,
+) -> Result<Connection<EventCursor, Event, EmptyFields, EmptyFields>> {
+    query(
+        after,
+        before,
+        first,
+        last,
+        |after: Option<EventCursor>, before: Option<EventCursor>, first, last| async move {
+            // Paginating backwards reads the events in descending order, then reverses them.
+            let backwards = first.is_none() && last.is_some();
+            let limit = first.or(last).unwrap_or(DEFAULT_PAGE_SIZE);
+
+            let page = Page {
+                after,
+                before,
+                limit: Some(limit),
+                backwards,
+            };
+
+            let events = find_events(db, select, start, end, &page).await?;
+
+            Ok::<_, Error>(into_connection(events, &page))
+        },
+    )
+    .await
+}
+
+/// The events found by [`find_events`] for a page with a limit, as a connection.
+fn into_connection(
+    mut events: Vec<Event>,
+    page: &Page,
+) -> Connection<EventCursor, Event, EmptyFields, EmptyFields> {
+    let limit = page.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    let has_more = events.len() > limit;
+
+    events.truncate(limit);
+
+    let mut connection = if page.backwards {
+        events.reverse();
+        Connection::new(has_more, page.before.is_some())
+    } else {
+        Connection::new(page.after.is_some(), has_more)
+    };
+
+    connection.edges.extend(
+        events
+            .into_iter()
+            .map(|event| Edge::new(event.cursor(), event)),
+    );
+
+    connection
+}
+
+/// An event shown on a day of the calendar.
+#[derive(SimpleObject, Debug)]
+pub struct EventPlacement {
+    /// The event, or its occurrence on this day if it recurs.
+    event: Event,
+    /// Row of the event within the week, the same on all its days in the week.
+    lane: u32,
+    /// Whether the event started on an earlier day.
+    continues_before: bool,
+    /// Whether the event goes on to a later day.
+    continues_after: bool,
+    /// Whether this day is today.
+    is_today: bool,
+}
+
+/// A day of the calendar.
+#[derive(SimpleObject, Debug)]
+pub struct CalendarDay {
+    date: Date,
+    /// Whether the day is in the month shown, not in an adjacent week.
+    in_month: bool,
+    is_today: bool,
+    /// Whether the day is a Saturday or Sunday.
+    is_weekend: bool,
+    /// Name of the public or school holiday on this day, if any.
+    holiday: Option<String>,
+    /// Whether there is school on this day, and why not.
+    school_day: SchoolDay,
+    /// Events on this day, by lane.
+    events: Vec<EventPlacement>,
+}
+
+/// A week of the calendar, from Monday to Sunday.
+#[derive(SimpleObject, Debug)]
+pub struct CalendarWeek {
+    /// ISO 8601 week number.
+    number: u32,
+    days: Vec<CalendarDay>,
+}
+
+/// The calendar of a month, laid out in whole weeks.
+#[derive(SimpleObject, Debug)]
+pub struct CalendarMonth {
+    year: i32,
+    /// The month (1-12).
+    month: u32,
+    weeks: Vec<CalendarWeek>,
 }
 
 #[derive(Default)]
@@ -40,50 +449,450 @@ impl EventsQuery {
     ///
     /// Returns events that overlap with the calendar view of the specified month
     /// (including days from adjacent weeks that appear in the month's calendar grid).
+    /// Recurring events are listed once for each occurrence.
     async fn events(
         &self,
         ctx: &Context<'_>,
         #[graphql(desc = "The year.")] year: i32,
         #[graphql(desc = "The month (1-12).")] month: u32,
+        #[graphql(desc = "Only events in these categories.")] categories: Option<Vec<u32>>,
     ) -> Result<Vec<Event>> {
         ctx.data_unchecked::<IntCounterVec>()
             .with(&labels! {"resource" => "events"})
             .inc();
 
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = EventsData::find().select_only();
+        let query = Event::columns(ctx, EventsData::find().select_only())
+            .filter(event_filter(ctx, categories.as_deref()));
+        let grid = MonthGrid::new(year, month).ok_or_else(|| Error::new("invalid date"))?;
 
-        select_columns!(ctx, query, Column);
+        find_events(db, query, grid.start(), Some(grid.end()), &Page::default()).await
+    }
 
-        let start = {
-            let start = NaiveDate::from_ymd_opt(year, month, 1)
-                .ok_or_else(|| Error::new("invalid date"))?
-                .and_hms_opt(0, 0, 0)
-                .unwrap();
+    /// Retrieve a single event by ID.
+    ///
+    /// A recurring event is returned as stored, with the times of its first occurrence.
+    async fn event(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The event ID.")] id: u32,
+    ) -> Result<Option<Event>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "event"})
+            .inc();
 
-            start - Duration::days(start.weekday().num_days_from_monday().into())
-        };
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let query = Event::columns(ctx, EventsData::find().select_only());
 
-        let end = {
-            let end = if month < 12 {
-                NaiveDate::from_ymd_opt(year, month + 1, 1)
-            } else {
-                NaiveDate::from_ymd_opt(year + 1, 1, 1)
-            }
-            .ok_or_else(|| Error::new("invalid date"))?
-            .and_hms_opt(0, 0, 0)
-            .unwrap();
+        query
+            .filter(Column::Id.eq(id))
+            .filter(event_filter(ctx, None))
+            .into_model::<Event>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)
+    }
 
-            end + Duration::days((6 - end.weekday().num_days_from_monday()).into())
-        };
+    /// Retrieve the events that have not ended yet, soonest first.
+    ///
+    /// Events already in progress at `from` are included before the ones starting later.
+    /// Pass the `endCursor` of the previous page as `after` to get more events.
+    async fn upcoming_events(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 10, desc = "The maximum number of events, at most 50.")] limit: u32,
+        #[graphql(desc = "The time to list events from, now by default.")] from: Option<DateTime>,
+        #[graphql(desc = "Only events in these categories.")] categories: Option<Vec<u32>>,
+        after: Option<String>,
+    ) -> Result<Connection<EventCursor, Event, EmptyFields, EmptyFields>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "upcoming_events"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let from = from.map_or_else(|| Local::now().naive_local(), |from| from.0);
+
+        paginate_events(
+            db,
+            Event::connection_columns(ctx, EventsData::find().select_only())
+                .filter(event_filter(ctx, categories.as_deref())),
+            from,
+            None,
+            after,
+            None,
+            Some(limit.min(MAX_UPCOMING_EVENTS) as i32),
+            None,
+        )
+        .await
+    }
+
+    /// Retrieve a paginated list of events overlapping a time range, ordered by start.
+    ///
+    /// Events starting before `from` but ending after it are included, as are events
+    /// ending exactly at `from`; events starting at `to` are not.
+    #[allow(clippy::too_many_arguments)]
+    async fn events_between(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Start of the range.")] from: DateTime,
+        #[graphql(desc = "End of the range, exclusive.")] to: DateTime,
+        #[graphql(desc = "Only events in these categories.")] categories: Option<Vec<u32>>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<Connection<EventCursor, Event, EmptyFields, EmptyFields>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "events_between"})
+            .inc();
+
+        if from >= to {
+            return Err(Error::new("`from` must be before `to`"));
+        }
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        paginate_events(
+            db,
+            Event::connection_columns(ctx, EventsData::find().select_only())
+                .filter(event_filter(ctx, categories.as_deref())),
+            from.0,
+            Some(to.0),
+            after,
+            before,
+            first,
+            last,
+        )
+        .await
+    }
+
+    /// Retrieve the event categories, in the order of the calendar legend.
+    ///
+    /// Private categories are only listed for editors.
+    async fn event_categories(&self, ctx: &Context<'_>) -> Result<Vec<EventCategory>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "event_categories"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = EventsCategories::find().select_only();
+
+        select_columns!(ctx, query, events_categories::Column);
+
+        if ctx.data_opt::<Editor>().is_none() {
+            query = query.filter(events_categories::Column::Public.eq(true));
+        }
 
         query
-            .filter(Column::DateTo.gte(start))
-            .filter(Column::DateFrom.lt(end))
-            .order_by(Column::DateFrom, Order::Asc)
-            .into_model::<Event>()
+            .order_by(events_categories::Column::Position, Order::Asc)
+            .order_by(events_categories::Column::Name, Order::Asc)
+            .into_model::<EventCategory>()
             .all(db.deref())
             .await
             .map_err(db_error)
     }
+
+    /// Retrieve the calendar of a month: its weeks, their days, and the events on each day.
+    ///
+    /// Multi-day events are placed in the same lane on each of their days within a week,
+    /// with flags telling whether they continue from or to another day.
+    async fn calendar_month(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The year.")] year: i32,
+        #[graphql(desc = "The month (1-12).")] month: u32,
+        #[graphql(desc = "Only events in these categories.")] categories: Option<Vec<u32>>,
+    ) -> Result<CalendarMonth> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "calendar_month"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let grid = MonthGrid::new(year, month).ok_or_else(|| Error::new("invalid date"))?;
+        let today = Local::now().date_naive();
+
+        let events = find_events(
+            db,
+            EventsData::find().filter(event_filter(ctx, categories.as_deref())),
+            grid.start(),
+            Some(grid.end()),
+            &Page::default(),
+        )
+        .await?;
+
+        let days: Vec<_> = events
+            .iter()
+            .map(|event| event_days(event.date_from.unwrap().0, event.date_to.unwrap().0))
+            .collect();
+
+        let school = SchoolCalendar::load(db.deref(), grid.start().date(), grid.last())
+            .await
+            .map_err(db_error)?;
+
+        let weeks = grid
+            .weeks()
+            .map(|monday| CalendarWeek {
+                number: monday.iso_week().week(),
+                days: layout_week(monday, &days)
+                    .into_iter()
+                    .zip(monday.iter_days())
+                    .map(|(placements, date)| {
+                        let school_day = school.day(date);
+                        let holiday = match school_day.kind {
+                            SchoolDayKind::Teaching
+                            | SchoolDayKind::WorkingSaturday
+                            | SchoolDayKind::Weekend => None,
+                            _ => school_day.name.clone(),
+                        };
+
+                        CalendarDay {
+                            date: Date(date),
+                            in_month: grid.in_month(date),
+                            is_today: date == today,
+                            is_weekend: is_weekend(date),
+                            holiday,
+                            school_day: school_day.into(),
+                            events: placements
+                                .into_iter()
+                                .map(|placement| EventPlacement {
+                                    event: events[placement.index].clone(),
+                                    lane: placement.lane as u32,
+                                    continues_before: placement.continues_before,
+                                    continues_after: placement.continues_after,
+                                    is_today: date == today,
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(CalendarMonth { year, month, weeks })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: &str = "2025-01-06 00:00";
+    const END: &str = "2025-01-13 00:00";
+
+    fn time(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn event(id: u32, from: &str, to: &str, rule: Option<&str>) -> Event {
+        Event {
+            id: Maybe(Some(id)),
+            date_from: Maybe(Some(DateTime(time(from)))),
+            date_to: Maybe(Some(DateTime(time(to)))),
+            title: Maybe(Some(format!("Event {id}"))),
+            description: Maybe(None),
+            color: Maybe(None),
+            category_id: Maybe(None),
+            recurrence_rule: Maybe(Some(rule.map(str::to_owned))),
+            occurrence: None,
+        }
+    }
+
+    fn exception(occurrence: &str, date_from: Option<&str>) -> events_exceptions::Model {
+        events_exceptions::Model {
+            events_id: 10,
+            occurrence: time(occurrence),
+            cancelled: date_from.is_none(),
+            date_from: date_from.map(time),
+            date_to: date_from.map(|date_from| time(date_from) + chrono::Duration::hours(1)),
+            title: None,
+            description: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    /// Events found for `page` between [`START`] and [`END`], with the single events selected
+    /// as the database query of [`find_events`] does.
+    fn find(page: &Page) -> Vec<Event> {
+        let (start, end) = (time(START), Some(time(END)));
+
+        let mut single: Vec<_> = [
+            // Ends exactly at the start of the range.
+            event(1, "2025-01-05 20:00", START, None),
+            // Starts at the same time as an occurrence, ordered by ID.
+            event(2, "2025-01-08 10:00", "2025-01-08 11:00", None),
+            event(3, "2025-01-08 10:00", "2025-01-08 12:00", None),
+            event(5, "2025-01-11 09:00", "2025-01-11 10:00", None),
+            // Starts exactly at the end of the range.
+            event(4, END, "2025-01-13 10:00", None),
+        ]
+        .into_iter()
+        .filter(|event| {
+            event.date_to.unwrap().0 >= start
+                && end.is_none_or(|end| event.date_from.unwrap().0 < end)
+                && page.contains(event)
+        })
+        .collect();
+
+        single.sort_by_key(Event::cursor);
+
+        if page.backwards {
+            single.reverse();
+        }
+
+        single.truncate(page.fetch_limit().unwrap_or(usize::MAX));
+
+        let recurring = vec![
+            event(
+                10,
+                "2025-01-01 10:00",
+                "2025-01-01 11:00",
+                Some("FREQ=DAILY"),
+            ),
+            event(
+                11,
+                "2025-01-07 12:00",
+                "2025-01-07 13:00",
+                Some("FREQ=SECONDLY"),
+            ),
+            event(
+                12,
+                "2025-01-06 09:00",
+                "2025-01-06 09:30",
+                Some("FREQ=WEEKLY;COUNT=1"),
+            ),
+        ];
+
+        let exceptions = HashMap::from([(
+            10,
+            vec![
+                // Two occurrences from before the range are moved into it.
+                exception("2025-01-01 10:00", Some("2025-01-12 15:00")),
+                exception("2025-01-02 10:00", Some("2025-01-11 08:00")),
+                exception("2025-01-07 10:00", Some("2025-01-07 18:00")),
+                exception("2025-01-09 10:00", None),
+            ],
+        )]);
+
+        add_occurrences(single, recurring, exceptions, start, end, page)
+    }
+
+    fn cursors(events: &[Event]) -> Vec<EventCursor> {
+        events.iter().map(Event::cursor).collect()
+    }
+
+    fn cursor(id: u32, from: &str) -> EventCursor {
+        EventCursor::new(time(from), id)
+    }
+
+    #[test]
+    fn lists_events_and_occurrences_in_order() {
+        assert_eq!(
+            cursors(&find(&Page::default())),
+            [
+                cursor(1, "2025-01-05 20:00"),
+                cursor(12, "2025-01-06 09:00"),
+                cursor(10, "2025-01-06 10:00"),
+                cursor(11, "2025-01-07 12:00"),
+                cursor(10, "2025-01-07 18:00"),
+                cursor(2, "2025-01-08 10:00"),
+                cursor(3, "2025-01-08 10:00"),
+                cursor(10, "2025-01-08 10:00"),
+                cursor(10, "2025-01-10 10:00"),
+                cursor(10, "2025-01-11 08:00"),
+                cursor(5, "2025-01-11 09:00"),
+                cursor(10, "2025-01-11 10:00"),
+                cursor(10, "2025-01-12 10:00"),
+                cursor(10, "2025-01-12 15:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn pages_forwards_through_all_events() {
+        let all = cursors(&find(&Page::default()));
+
+        for limit in 1..=all.len() + 1 {
+            let mut listed = Vec::new();
+            let mut after = None;
+
+            loop {
+                let page = Page {
+                    after,
+                    limit: Some(limit),
+                    ..Page::default()
+                };
+                let connection = into_connection(find(&page), &page);
+
+                assert_eq!(connection.has_previous_page, after.is_some());
+                assert!(connection.edges.len() <= limit);
+
+                listed.extend(connection.edges.iter().map(|edge| edge.cursor));
+                after = listed.last().copied();
+
+                if !connection.has_next_page {
+                    break;
+                }
+            }
+
+            assert_eq!(listed, all, "pages of {limit}");
+        }
+    }
+
+    #[test]
+    fn pages_backwards_through_all_events() {
+        let all = cursors(&find(&Page::default()));
+
+        for limit in 1..=all.len() + 1 {
+            let mut pages = Vec::new();
+            let mut before = None;
+
+            loop {
+                let page = Page {
+                    before,
+                    limit: Some(limit),
+                    backwards: true,
+                    ..Page::default()
+                };
+                let connection = into_connection(find(&page), &page);
+                let listed: Vec<_> = connection.edges.iter().map(|edge| edge.cursor).collect();
+
+                assert_eq!(connection.has_next_page, before.is_some());
+                assert!(listed.is_sorted());
+
+                before = listed.first().copied();
+                pages.push(listed);
+
+                if !connection.has_previous_page {
+                    break;
+                }
+            }
+
+            pages.reverse();
+
+            assert_eq!(pages.concat(), all, "pages of {limit}");
+        }
+    }
+
+    #[test]
+    fn pages_between_cursors() {
+        let page = Page {
+            after: Some(cursor(2, "2025-01-08 10:00")),
+            before: Some(cursor(10, "2025-01-11 10:00")),
+            limit: Some(3),
+            backwards: false,
+        };
+        let connection = into_connection(find(&page), &page);
+        let listed: Vec<_> = connection.edges.iter().map(|edge| edge.cursor).collect();
+
+        assert_eq!(
+            listed,
+            [
+                cursor(3, "2025-01-08 10:00"),
+                cursor(10, "2025-01-08 10:00"),
+                cursor(10, "2025-01-10 10:00"),
+            ]
+        );
+        assert!(connection.has_previous_page && connection.has_next_page);
+    }
 }
@@ -0,0 +1,135 @@
+use crate::{
+    Config,
+    entity::image_placeholders::{Column, Entity as ImagePlaceholders},
+    media::{ImageMeta, VariantFormat, gallery_entries, image_directory, srcset},
+    utils::db_error,
+};
+use async_graphql::{ComplexObject, Context, Result, SimpleObject};
+use sea_orm::{DatabaseTransaction, FromQueryResult, prelude::*, query::QuerySelect};
+use serde_json::Value as Json;
+use std::sync::Arc;
+
+fn mime_type(file: &str) -> Option<&'static str> {
+    let (_, extension) = file.rsplit_once('.')?;
+
+    Some(match extension.to_ascii_lowercase().as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "avif" => "image/avif",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    })
+}
+
+/// A low-detail preview of an image, shown while the image itself is loading.
+#[derive(SimpleObject, FromQueryResult, Debug, Clone)]
+pub struct ImagePlaceholder {
+    /// [BlurHash](https://blurha.sh) of the image.
+    pub blurhash: String,
+    /// Most common colour of the image, as `#rrggbb`.
+    pub dominant_color: String,
+}
+
+impl ImagePlaceholder {
+    /// Look up the placeholder of an image by its kind and file name.
+    ///
+    /// Returns `None` until the background job has processed the image.
+    pub async fn find(ctx: &Context<'_>, kind: &str, file: &str) -> Result<Option<Self>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let Some(directory) = image_directory(kind) else {
+            return Ok(None);
+        };
+
+        ImagePlaceholders::find_by_id((directory.to_owned(), file.to_owned()))
+            .select_only()
+            .column(Column::Blurhash)
+            .column(Column::DominantColor)
+            .into_model::<Self>()
+            .one(db.as_ref())
+            .await
+            .map_err(db_error)
+    }
+}
+
+/// An image served from the storage server.
+#[derive(SimpleObject, Debug, Clone)]
+#[graphql(complex)]
+pub struct Image {
+    /// Full image URL.
+    pub url: String,
+    /// Alternative text for screen readers.
+    pub alt: Option<String>,
+    /// Caption shown with the image.
+    pub caption: Option<String>,
+    /// Width in pixels, if known.
+    pub width: Option<u32>,
+    /// Height in pixels, if known.
+    pub height: Option<u32>,
+    /// MIME type, e.g. `image/jpeg`.
+    pub mime_type: Option<String>,
+    /// File name in the storage directory, as stored in posts, authors and colleagues.
+    pub file: String,
+    #[graphql(skip)]
+    pub kind: &'static str,
+}
+
+impl Image {
+    /// `kind` is one of the image kinds served by `/img`, e.g. `posts`.
+    pub fn new(config: &Config, kind: &'static str, file: &str, meta: ImageMeta) -> Self {
+        let directory = image_directory(kind).expect("Unknown image kind");
+
+        Self {
+            url: format!("{}/{directory}/{file}", config.storage_base_url),
+            alt: meta.alt.filter(|alt| !alt.is_empty()),
+            caption: meta.caption.filter(|caption| !caption.is_empty()),
+            width: meta.width,
+            height: meta.height,
+            mime_type: meta
+                .mime_type
+                .or_else(|| mime_type(file).map(str::to_owned)),
+            kind,
+            file: file.to_owned(),
+        }
+    }
+
+    /// Parse a gallery stored as JSON, see [`gallery_entries`].
+    pub fn gallery(config: &Config, kind: &'static str, json: &Json) -> Option<Vec<Self>> {
+        Some(
+            gallery_entries(json)?
+                .into_iter()
+                .map(|(file, meta)| Self::new(config, kind, file, meta))
+                .collect(),
+        )
+    }
+}
+
+#[ComplexObject]
+impl Image {
+    /// Resized variants for the `srcset` attribute, or null if resizing is not available.
+    async fn srcset(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Widths to include. Defaults to all allowed widths.")] widths: Option<
+            Vec<u32>,
+        >,
+        #[graphql(desc = "Output format. Defaults to the original format.")] format: Option<
+            VariantFormat,
+        >,
+    ) -> Option<String> {
+        srcset(
+            ctx.data_unchecked::<Config>(),
+            self.kind,
+            &self.file,
+            widths.as_deref(),
+            format,
+        )
+    }
+
+    /// Placeholder to show while the image is loading, or null if it is not computed yet.
+    async fn placeholder(&self, ctx: &Context<'_>) -> Result<Option<ImagePlaceholder>> {
+        ImagePlaceholder::find(ctx, self.kind, &self.file).await
+    }
+}
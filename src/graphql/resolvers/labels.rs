@@ -1,7 +1,6 @@
-use super::Post;
+use super::{Post, currently_pinned};
 use crate::{
     entity::{
-        posts_data,
         posts_labels::{self, Entity as PostsLabels},
         posts_pivot_labels_data,
     },
@@ -33,11 +32,11 @@ pub struct Label {
 impl Label {
     /// Paginated list of posts with this label.
     ///
-    /// Use `featured: true` to filter only featured posts.
+    /// Use `featured: true` to filter only posts that are currently pinned.
     async fn posts(
         &self,
         ctx: &Context<'_>,
-        #[graphql(default = false, desc = "Filter to only featured posts.")] featured: bool,
+        #[graphql(default = false, desc = "Filter to only currently pinned posts.")] featured: bool,
         after: Option<String>,
         before: Option<String>,
         first: Option<i32>,
@@ -47,7 +46,7 @@ impl Label {
 
         let condition = {
             let condition = if featured {
-                Some(posts_data::Column::Featured.eq(true))
+                Some(currently_pinned())
             } else {
                 None
             };
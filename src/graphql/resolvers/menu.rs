@@ -1,7 +1,9 @@
 use crate::entity::{
     menu_items::{Column, Entity as MenuItems},
+    menu_items_translations::{self, Entity as MenuItemsTranslations},
     pages,
 };
+use crate::graphql::{Locale, available_locales};
 use crate::select_columns;
 use crate::utils::{Maybe, db_error};
 use async_graphql::{ComplexObject, Context, Object, Result, SimpleObject};
@@ -27,6 +29,66 @@ pub struct MenuItem {
     link: Maybe<Option<String>>,
     #[graphql(skip)]
     page_id: Option<u32>,
+    #[graphql(skip)]
+    #[sea_orm(skip)]
+    locale: Locale,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct MenuItemTranslation {
+    menu_items_id: u32,
+    name: String,
+    link: Option<String>,
+}
+
+impl MenuItem {
+    /// Replace the selected text fields of `items` with their translation to `locale`
+    /// and remember the locale for their children.
+    async fn translate(
+        items: &mut [MenuItem],
+        db: &DatabaseTransaction,
+        locale: Locale,
+    ) -> Result<()> {
+        if !locale.is_default() && !items.is_empty() {
+            let translations = MenuItemsTranslations::find()
+                .select_only()
+                .column(menu_items_translations::Column::MenuItemsId)
+                .column(menu_items_translations::Column::Name)
+                .column(menu_items_translations::Column::Link)
+                .filter(
+                    menu_items_translations::Column::MenuItemsId
+                        .is_in(items.iter().filter_map(|item| *item.id)),
+                )
+                .filter(menu_items_translations::Column::Locale.eq(locale.as_str()))
+                .into_model::<MenuItemTranslation>()
+                .all(db)
+                .await
+                .map_err(db_error)?;
+
+            for item in items.iter_mut() {
+                let Some(translation) = translations
+                    .iter()
+                    .find(|translation| *item.id == Some(translation.menu_items_id))
+                else {
+                    continue;
+                };
+
+                if item.name.is_some() {
+                    item.name = Maybe(Some(translation.name.clone()));
+                }
+
+                if item.link.is_some() && translation.link.is_some() {
+                    item.link = Maybe(Some(translation.link.clone()));
+                }
+            }
+        }
+
+        for item in items {
+            item.locale = locale.clone();
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveColumn)]
@@ -50,10 +112,25 @@ impl MenuItem {
             .map(|(slug,)| slug))
     }
 
+    /// Locales this menu item is available in, starting with the default one.
+    async fn available_locales(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        available_locales(
+            db,
+            MenuItemsTranslations::find()
+                .select_only()
+                .column(menu_items_translations::Column::Locale)
+                .filter(menu_items_translations::Column::MenuItemsId.eq(self.id.unwrap()))
+                .order_by(menu_items_translations::Column::Locale, Order::Asc),
+        )
+        .await
+    }
+
     /// Nested child menu items (for dropdown menus).
     async fn children(&self, ctx: &Context<'_>) -> Result<Vec<MenuItem>> {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = MenuItems::find().select_only();
+        let mut query = MenuItems::find().select_only().column(Column::Id);
 
         select_columns!(ctx, query, Column);
         select_columns!(ctx, query,
@@ -61,13 +138,17 @@ impl MenuItem {
             "link" => Column::PageId,
             "children" => Column::Id);
 
-        query
+        let mut items = query
             .filter(Column::ParentId.eq(self.id.unwrap()))
             .order_by(Column::Lft, Order::Asc)
             .into_model::<MenuItem>()
             .all(db.deref())
             .await
-            .map_err(db_error)
+            .map_err(db_error)?;
+
+        MenuItem::translate(&mut items, db, self.locale.clone()).await?;
+
+        Ok(items)
     }
 }
 
@@ -79,13 +160,19 @@ impl MenuQuery {
     /// Retrieve the navigation menu structure.
     ///
     /// Returns top-level menu items. Use the `children` field to access nested items.
-    async fn menu(&self, ctx: &Context<'_>) -> Result<Vec<MenuItem>> {
+    /// Names and links are returned in `locale` (or the `Accept-Language` header),
+    /// falling back to Hungarian.
+    async fn menu(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The preferred locale, e.g. \"en\".")] locale: Option<String>,
+    ) -> Result<Vec<MenuItem>> {
         ctx.data_unchecked::<IntCounterVec>()
             .with(&labels! {"resource" => "menu"})
             .inc();
 
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = MenuItems::find().select_only();
+        let mut query = MenuItems::find().select_only().column(Column::Id);
 
         select_columns!(ctx, query, Column);
         select_columns!(ctx, query,
@@ -93,12 +180,16 @@ impl MenuQuery {
             "link" => Column::PageId,
             "children" => Column::Id);
 
-        query
+        let mut items = query
             .filter(Column::ParentId.is_null())
             .order_by(Column::Lft, Order::Asc)
             .into_model::<MenuItem>()
             .all(db.deref())
             .await
-            .map_err(db_error)
+            .map_err(db_error)?;
+
+        MenuItem::translate(&mut items, db, Locale::resolve(ctx, locale)).await?;
+
+        Ok(items)
     }
 }
@@ -1,19 +1,37 @@
+mod albums;
 mod archive;
+mod attachments;
 mod author;
 mod canteen;
 mod colleagues;
 mod events;
+mod images;
 mod labels;
 mod menu;
 mod pages;
+mod pins;
 mod posts;
+mod revisions;
+mod school;
+mod series;
+mod uploads;
+mod views;
 
+pub use albums::*;
 pub use archive::*;
+pub use attachments::*;
 pub use author::*;
 pub use canteen::*;
 pub use colleagues::*;
 pub use events::*;
+pub use images::*;
 pub use labels::*;
 pub use menu::*;
 pub use pages::*;
+pub use pins::*;
 pub use posts::*;
+pub use revisions::*;
+pub use school::*;
+pub use series::*;
+pub use uploads::*;
+pub use views::*;
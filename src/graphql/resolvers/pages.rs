@@ -1,13 +1,27 @@
-use crate::entity::pages::{Column, Entity as Pages};
+use crate::Config;
+use crate::content::{
+    Block, ContentCache, ContentKind, ContentRenderer, ShortcodeExpander, parse_blocks,
+};
+use crate::entity::{
+    pages::{Column, Entity as Pages},
+    pages_translations::{self, Entity as PagesTranslations},
+    sea_orm_active_enums::ContentFormat,
+};
+use crate::graphql::{EditorGuard, Locale, available_locales, types::DateTime};
 use crate::select_columns;
 use crate::utils::{Maybe, db_error};
-use async_graphql::{Context, Object, Result, SimpleObject};
+use async_graphql::{ComplexObject, Context, Error, Object, Result, SimpleObject};
 use prometheus::{IntCounterVec, labels};
-use sea_orm::{DatabaseTransaction, FromQueryResult, entity::prelude::*, query::QuerySelect};
+use sea_orm::{
+    DatabaseTransaction, FromQueryResult, Order,
+    entity::prelude::*,
+    query::{QueryOrder, QuerySelect},
+};
 use std::{ops::Deref, sync::Arc};
 
 /// A static page.
 #[derive(SimpleObject, Debug, FromQueryResult)]
+#[graphql(complex)]
 pub struct Page {
     /// Unique identifier.
     id: Maybe<u32>,
@@ -17,10 +31,92 @@ pub struct Page {
     name: Maybe<String>,
     /// Page title for display.
     title: Maybe<String>,
-    /// Page content (HTML or markdown).
+    #[graphql(skip)]
     content: Maybe<String>,
+    #[graphql(skip)]
+    content_format: Maybe<ContentFormat>,
     /// Additional structured data as JSON.
     extras: Maybe<Json>,
+    #[graphql(skip)]
+    updated_at: Maybe<DateTime>,
+    #[graphql(skip)]
+    #[sea_orm(skip)]
+    locale: Locale,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PageTranslation {
+    title: String,
+    content: String,
+    updated_at: DateTime,
+}
+
+impl Page {
+    async fn rendered_content(&self, ctx: &Context<'_>) -> Result<String> {
+        let (Some(content), Some(format), Some(id), Some(updated_at)) = (
+            &*self.content,
+            *self.content_format,
+            *self.id,
+            *self.updated_at,
+        ) else {
+            return Err(Error::new("Database error: content not selected"));
+        };
+
+        let html = ctx.data_unchecked::<ContentCache>().get_or_render(
+            ContentKind::Page,
+            id,
+            &self.locale,
+            updated_at.0,
+            || {
+                ctx.data_unchecked::<ContentRenderer>()
+                    .render(content, format)
+            },
+        );
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        ShortcodeExpander::new(db, ctx.data_unchecked::<Config>())
+            .expand(&html)
+            .await
+    }
+}
+
+#[ComplexObject]
+impl Page {
+    /// Page content as HTML (rendered from Markdown if needed), sanitised for safe
+    /// embedding, with asset URLs resolved.
+    async fn content(&self, ctx: &Context<'_>) -> Result<String> {
+        self.rendered_content(ctx).await
+    }
+
+    /// The page content split into structured blocks.
+    async fn blocks(&self, ctx: &Context<'_>) -> Result<Vec<Block>> {
+        Ok(parse_blocks(&self.rendered_content(ctx).await?))
+    }
+
+    /// Page content exactly as stored, without sanitisation.
+    #[graphql(guard = "EditorGuard")]
+    async fn raw_content(&self) -> Result<String> {
+        self.content
+            .0
+            .clone()
+            .ok_or_else(|| Error::new("Database error: content not selected"))
+    }
+
+    /// Locales this page is available in, starting with the default one.
+    async fn available_locales(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        available_locales(
+            db,
+            PagesTranslations::find()
+                .select_only()
+                .column(pages_translations::Column::Locale)
+                .filter(pages_translations::Column::PagesId.eq(self.id.unwrap()))
+                .order_by(pages_translations::Column::Locale, Order::Asc),
+        )
+        .await
+    }
 }
 
 #[derive(Default)]
@@ -29,25 +125,70 @@ pub struct PagesQuery;
 #[Object]
 impl PagesQuery {
     /// Retrieve a page by its URL slug.
+    ///
+    /// Translated fields are returned in `locale` (or the `Accept-Language` header),
+    /// falling back to Hungarian.
     async fn page(
         &self,
         ctx: &Context<'_>,
         #[graphql(desc = "The page's URL slug (e.g., \"about\", \"contact\").")] slug: String,
+        #[graphql(desc = "The preferred locale, e.g. \"en\".")] locale: Option<String>,
     ) -> Result<Option<Page>> {
         ctx.data_unchecked::<IntCounterVec>()
             .with(&labels! {"resource" => "page"})
             .inc();
 
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = Pages::find().select_only();
+        let mut query = Pages::find().select_only().column(Column::Id);
 
         select_columns!(ctx, query, Column);
+        select_columns!(ctx, query,
+            "blocks" | "rawContent" => Column::Content,
+            "blocks" | "content" => Column::UpdatedAt,
+            "blocks" | "content" => Column::ContentFormat);
 
-        query
+        let Some(mut page) = query
             .filter(Column::Slug.eq(slug))
             .into_model::<Page>()
             .one(db.deref())
             .await
-            .map_err(db_error)
+            .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        let locale = Locale::resolve(ctx, locale);
+
+        if !locale.is_default() {
+            let translation = PagesTranslations::find()
+                .select_only()
+                .column(pages_translations::Column::Title)
+                .column(pages_translations::Column::Content)
+                .column(pages_translations::Column::UpdatedAt)
+                .filter(pages_translations::Column::PagesId.eq(page.id.unwrap()))
+                .filter(pages_translations::Column::Locale.eq(locale.as_str()))
+                .into_model::<PageTranslation>()
+                .one(db.deref())
+                .await
+                .map_err(db_error)?;
+
+            if let Some(translation) = translation {
+                if page.title.is_some() {
+                    page.title = Maybe(Some(translation.title));
+                }
+
+                if page.content.is_some() {
+                    page.content = Maybe(Some(translation.content));
+                }
+
+                if page.updated_at.is_some() {
+                    page.updated_at = Maybe(Some(translation.updated_at));
+                }
+
+                page.locale = locale;
+            }
+        }
+
+        Ok(Some(page))
     }
 }
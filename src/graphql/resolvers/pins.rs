@@ -0,0 +1,81 @@
+use super::Post;
+use crate::{
+    entity::{
+        posts_data::{self, Entity as PostsData},
+        posts_pins::{self, Entity as PostsPins},
+    },
+    graphql::Locale,
+    utils::db_error,
+};
+use async_graphql::{Context, Object, Result};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    Condition, DatabaseTransaction, JoinType,
+    prelude::*,
+    query::{Order, QueryOrder, QuerySelect},
+    sea_query::{Expr, Query, SimpleExpr},
+};
+use std::{ops::Deref, sync::Arc};
+
+const DEFAULT_SLOT: &str = "homepage";
+
+fn not_expired() -> Condition {
+    Condition::any()
+        .add(posts_pins::Column::PinnedUntil.is_null())
+        .add(Expr::col(posts_pins::Column::PinnedUntil).gt(Expr::current_timestamp()))
+}
+
+/// Matches posts that are currently pinned in any slot.
+///
+/// This is what the `featured` filter of the post listings means.
+pub fn currently_pinned() -> SimpleExpr {
+    posts_data::Column::Id.in_subquery(
+        Query::select()
+            .column(posts_pins::Column::PostsId)
+            .from(PostsPins)
+            .cond_where(not_expired())
+            .to_owned(),
+    )
+}
+
+#[derive(Default)]
+pub struct PinsQuery;
+
+#[Object]
+impl PinsQuery {
+    /// Retrieve the posts currently pinned to a slot, in their pinned order.
+    ///
+    /// Pins past their `pinned_until` time are left out.
+    async fn pinned_posts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default_with = "DEFAULT_SLOT.to_owned()", desc = "The pin slot.")] slot: String,
+    ) -> Result<Vec<Post>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "pinned_posts"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::Id);
+
+        query = Post::columns(ctx, query);
+
+        let mut posts = query
+            .join_rev(JoinType::Join, posts_pins::Relation::Posts.def())
+            .filter(posts_pins::Column::Slot.eq(slot))
+            .filter(not_expired())
+            .filter(posts_data::Column::Published.eq(true))
+            .order_by(posts_pins::Column::Position, Order::Asc)
+            .order_by(posts_pins::Column::Id, Order::Asc)
+            .into_model::<Post>()
+            .all(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        Post::translate(&mut posts, db, &Locale::resolve(ctx, None)).await?;
+
+        Ok(posts)
+    }
+}
@@ -1,14 +1,31 @@
-use super::{Author, Label};
+use super::{
+    Attachment, Author, DiffGranularity, Image, ImagePlaceholder, Label, Revision, RevisionDiff,
+    SeriesEntry, currently_pinned,
+};
 use crate::{
     Config,
+    content::{
+        Block, ContentCache, ContentKind, ContentRenderer, ShortcodeExpander, excerpt,
+        parse_blocks, plain_text, reading_time, word_count,
+    },
     entity::{
+        posts_attachments::{self, Entity as PostsAttachments},
         posts_authors::{self, Entity as PostsAuthors},
         posts_data::{self, Entity as PostsData},
         posts_labels::{self, Entity as PostsLabels},
-        posts_pivot_labels_data,
+        posts_pivot_authors_data, posts_pivot_labels_data,
+        posts_pivot_series_data::{self, Entity as PostsPivotSeriesData},
+        posts_revisions::{self, Entity as PostsRevisions},
+        posts_translations::{self, Entity as PostsTranslations},
+        posts_views::{self, Entity as PostsViews},
+        sea_orm_active_enums::ContentFormat,
+    },
+    graphql::{
+        EditorGuard, Locale, available_locales,
+        types::{Date, DateTime, PostCursor},
     },
-    graphql::types::{Date, PostCursor},
-    select_columns,
+    media::{ImageMeta, VariantFormat, srcset},
+    select_columns, select_columns_connection,
     utils::{Maybe, create_paginated_posts, db_error},
 };
 use async_graphql::{
@@ -20,6 +37,7 @@ use sea_orm::{
     Condition, DatabaseTransaction, FromQueryResult,
     prelude::*,
     query::{JoinType, Order, QueryOrder, QuerySelect},
+    sea_query::Expr,
 };
 use std::{ops::Deref, sync::Arc};
 
@@ -35,21 +53,239 @@ pub struct Post {
     pub color: Maybe<String>,
     /// Short description or excerpt.
     pub description: Maybe<Option<String>>,
-    /// Full post content.
+    #[graphql(skip)]
     pub content: Maybe<String>,
     #[graphql(skip)]
+    pub content_format: Maybe<ContentFormat>,
+    #[graphql(skip)]
     pub index_image: Maybe<String>,
     #[graphql(skip)]
+    pub index_image_meta: Maybe<Option<Json>>,
+    #[graphql(skip)]
     pub author_id: Maybe<u32>,
     #[graphql(skip)]
     pub images: Maybe<serde_json::Value>,
     /// Publication date.
     pub date: Maybe<Date>,
+    #[graphql(skip)]
+    pub updated_at: Maybe<DateTime>,
+    #[graphql(skip)]
+    #[sea_orm(skip)]
+    pub locale: Locale,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct PostTranslation {
+    posts_id: u32,
+    title: String,
+    description: Option<String>,
+    content: Option<String>,
+    updated_at: DateTime,
+}
+
+impl Post {
+    /// Add the columns needed by the fields selected on a post to `query`.
+    pub fn columns(ctx: &Context<'_>, mut query: Select<PostsData>) -> Select<PostsData> {
+        select_columns!(ctx, query, posts_data::Column);
+        select_columns!(ctx, query,
+            "author" => posts_data::Column::AuthorId,
+            "cover" | "indexImagePlaceholder" | "indexImageSrcset" => posts_data::Column::IndexImage,
+            "cover" => posts_data::Column::IndexImageMeta,
+            "gallery" => posts_data::Column::Images,
+            "blocks" | "excerpt" | "rawContent" | "readingTime" | "wordCount" => posts_data::Column::Content,
+            "blocks" | "content" => posts_data::Column::UpdatedAt,
+            "blocks" | "content" | "excerpt" | "readingTime" | "wordCount" => posts_data::Column::ContentFormat,
+            "blocks" | "content" => posts_data::Column::Images,
+            "attachments" | "authors" | "availableLocales" | "blocks" | "content" | "labels" | "revisions" | "revisionDiff" | "series" | "viewCount" => posts_data::Column::Id);
+
+        query
+    }
+
+    /// Same as [`Post::columns`] for posts selected through a connection.
+    pub fn connection_columns(
+        ctx: &Context<'_>,
+        mut query: Select<PostsData>,
+    ) -> Select<PostsData> {
+        select_columns_connection!(ctx, query, posts_data::Column);
+        select_columns_connection!(ctx, query,
+            "author" => posts_data::Column::AuthorId,
+            "cover" | "indexImagePlaceholder" | "indexImageSrcset" => posts_data::Column::IndexImage,
+            "cover" => posts_data::Column::IndexImageMeta,
+            "gallery" => posts_data::Column::Images,
+            "blocks" | "excerpt" | "rawContent" | "readingTime" | "wordCount" => posts_data::Column::Content,
+            "blocks" | "content" => posts_data::Column::UpdatedAt,
+            "blocks" | "content" | "excerpt" | "readingTime" | "wordCount" => posts_data::Column::ContentFormat,
+            "blocks" | "content" => posts_data::Column::Images,
+            "attachments" | "authors" | "availableLocales" | "blocks" | "content" | "labels" | "revisions" | "revisionDiff" | "series" | "viewCount" => posts_data::Column::Id);
+
+        query
+    }
+
+    /// Replace the selected text fields of `posts` with their translation to `locale`.
+    ///
+    /// Posts without a translation keep the default locale. The `id` column must be selected.
+    pub async fn translate(
+        posts: &mut [Post],
+        db: &DatabaseTransaction,
+        locale: &Locale,
+    ) -> Result<()> {
+        if locale.is_default() || posts.is_empty() {
+            return Ok(());
+        }
+
+        let translations = PostsTranslations::find()
+            .select_only()
+            .column(posts_translations::Column::PostsId)
+            .column(posts_translations::Column::Title)
+            .column(posts_translations::Column::Description)
+            .column(posts_translations::Column::Content)
+            .column(posts_translations::Column::UpdatedAt)
+            .filter(
+                posts_translations::Column::PostsId.is_in(posts.iter().filter_map(|post| *post.id)),
+            )
+            .filter(posts_translations::Column::Locale.eq(locale.as_str()))
+            .into_model::<PostTranslation>()
+            .all(db)
+            .await
+            .map_err(db_error)?;
+
+        for post in posts {
+            let Some(translation) = translations
+                .iter()
+                .find(|translation| *post.id == Some(translation.posts_id))
+            else {
+                continue;
+            };
+
+            if post.title.is_some() {
+                post.title = Maybe(Some(translation.title.clone()));
+            }
+
+            if post.description.is_some() && translation.description.is_some() {
+                post.description = Maybe(Some(translation.description.clone()));
+            }
+
+            if let (Some(_), Some(content)) = (&*post.content, &translation.content) {
+                post.content = Maybe(Some(content.clone()));
+            }
+
+            // Untranslated content is kept, so the post may have changed after its translation.
+            if let Some(updated_at) = *post.updated_at {
+                post.updated_at = Maybe(Some(updated_at.max(translation.updated_at)));
+            }
+
+            post.locale = locale.clone();
+        }
+
+        Ok(())
+    }
+}
+
+impl Post {
+    async fn rendered_content(&self, ctx: &Context<'_>) -> Result<String> {
+        let (Some(content), Some(format), Some(id), Some(updated_at)) = (
+            &*self.content,
+            *self.content_format,
+            *self.id,
+            *self.updated_at,
+        ) else {
+            return Err(Error::new("Database error: content not selected"));
+        };
+
+        let html = ctx.data_unchecked::<ContentCache>().get_or_render(
+            ContentKind::Post,
+            id,
+            &self.locale,
+            updated_at.0,
+            || {
+                ctx.data_unchecked::<ContentRenderer>()
+                    .render(content, format)
+            },
+        );
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let config = ctx.data_unchecked::<Config>();
+        let images = self.gallery_images(config)?;
+
+        ShortcodeExpander::new(db, config)
+            .with_gallery(&images)
+            .expand(&html)
+            .await
+    }
+
+    fn plain_text(&self) -> Result<String> {
+        let (Some(content), Some(format)) = (&*self.content, *self.content_format) else {
+            return Err(Error::new("Database error: content not selected"));
+        };
+
+        Ok(plain_text(content, format))
+    }
+
+    fn gallery_images(&self, config: &Config) -> Result<Vec<Image>> {
+        self.images
+            .as_ref()
+            .and_then(|images| Image::gallery(config, "posts", images))
+            .ok_or_else(|| Error::new("invalid data in database"))
+    }
 }
 
 #[ComplexObject]
 impl Post {
+    /// Full post content as HTML (rendered from Markdown if needed), sanitised for
+    /// safe embedding, with asset URLs resolved.
+    async fn content(&self, ctx: &Context<'_>) -> Result<String> {
+        self.rendered_content(ctx).await
+    }
+
+    /// The post content split into structured blocks.
+    async fn blocks(&self, ctx: &Context<'_>) -> Result<Vec<Block>> {
+        Ok(parse_blocks(&self.rendered_content(ctx).await?))
+    }
+
+    /// Number of words in the post content.
+    async fn word_count(&self) -> Result<usize> {
+        Ok(word_count(&self.plain_text()?))
+    }
+
+    /// Estimated reading time of the post content in minutes.
+    async fn reading_time(&self) -> Result<usize> {
+        Ok(reading_time(word_count(&self.plain_text()?)))
+    }
+
+    /// The beginning of the post content as plain text, cut at a word boundary.
+    async fn excerpt(
+        &self,
+        #[graphql(default = 200, desc = "The maximum length in characters.")] length: usize,
+    ) -> Result<String> {
+        Ok(excerpt(&self.plain_text()?, length))
+    }
+
+    /// Full post content exactly as stored, without sanitisation.
+    #[graphql(guard = "EditorGuard")]
+    async fn raw_content(&self) -> Result<String> {
+        self.content
+            .0
+            .clone()
+            .ok_or_else(|| Error::new("Database error: content not selected"))
+    }
+
+    /// Locales this post is available in, starting with the default one.
+    async fn available_locales(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        available_locales(
+            db,
+            PostsTranslations::find()
+                .select_only()
+                .column(posts_translations::Column::Locale)
+                .filter(posts_translations::Column::PostsId.eq(self.id.unwrap()))
+                .order_by(posts_translations::Column::Locale, Order::Asc),
+        )
+        .await
+    }
+
     /// Main image URL for the post.
+    #[graphql(deprecation = "Use `cover` instead.")]
     async fn index_image(&self, ctx: &Context<'_>) -> Result<String> {
         let config = ctx.data_unchecked::<Config>();
 
@@ -60,44 +296,91 @@ impl Post {
         Ok(format!("{}/posts_images/{image}", config.storage_base_url))
     }
 
+    /// Resized variants of the main image for the `srcset` attribute, or null if
+    /// resizing is not available.
+    async fn index_image_srcset(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "Widths to include. Defaults to all allowed widths.")] widths: Option<
+            Vec<u32>,
+        >,
+        #[graphql(desc = "Output format. Defaults to the original format.")] format: Option<
+            VariantFormat,
+        >,
+    ) -> Result<Option<String>> {
+        let Some(ref image) = *self.index_image else {
+            return Err(Error::new("Database error: index image not selected"));
+        };
+
+        if image.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(srcset(
+            ctx.data_unchecked::<Config>(),
+            "posts",
+            image,
+            widths.as_deref(),
+            format,
+        ))
+    }
+
+    /// Placeholder to show while the main image is loading, or null if there is no
+    /// image or its placeholder is not computed yet.
+    async fn index_image_placeholder(&self, ctx: &Context<'_>) -> Result<Option<ImagePlaceholder>> {
+        let Some(ref image) = *self.index_image else {
+            return Err(Error::new("Database error: index image not selected"));
+        };
+
+        if image.is_empty() {
+            return Ok(None);
+        }
+
+        ImagePlaceholder::find(ctx, "posts", image).await
+    }
+
+    /// Main image of the post, with its metadata.
+    async fn cover(&self, ctx: &Context<'_>) -> Result<Option<Image>> {
+        let Some(ref image) = *self.index_image else {
+            return Err(Error::new("Database error: index image not selected"));
+        };
+
+        if image.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(Image::new(
+            ctx.data_unchecked::<Config>(),
+            "posts",
+            image,
+            ImageMeta::from_json(self.index_image_meta.as_ref().and_then(Option::as_ref)),
+        )))
+    }
+
     /// Additional image URLs associated with the post.
+    #[graphql(deprecation = "Use `gallery` instead.")]
     async fn images(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
-        let config = ctx.data_unchecked::<Config>();
+        Ok(self
+            .gallery_images(ctx.data_unchecked::<Config>())?
+            .into_iter()
+            .map(|image| image.url)
+            .collect())
+    }
 
-        match &*self.images {
-            Some(Json::Array(arr)) => Ok(arr
-                .iter()
-                .filter(|elem| elem.is_string())
-                .map(|elem| {
-                    format!(
-                        "{}/posts_images/{}",
-                        config.storage_base_url,
-                        elem.as_str().unwrap()
-                    )
-                })
-                .collect()),
-            Some(Json::Object(map)) => Ok(map
-                .values()
-                .filter(|elem| elem.is_string())
-                .map(|elem| {
-                    format!(
-                        "{}/posts_images/{}",
-                        config.storage_base_url,
-                        elem.as_str().unwrap()
-                    )
-                })
-                .collect()),
-            _ => Err(Error::new("invalid data in database")),
-        }
+    /// Additional images associated with the post, with their metadata.
+    async fn gallery(&self, ctx: &Context<'_>) -> Result<Vec<Image>> {
+        self.gallery_images(ctx.data_unchecked::<Config>())
     }
 
-    /// The author of this post.
+    /// The primary author of this post.
     async fn author(&self, ctx: &Context<'_>) -> Result<Option<Author>> {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
         let mut query = PostsAuthors::find().select_only();
 
         select_columns!(ctx, query, posts_authors::Column);
-        select_columns!(ctx, query, "posts" => posts_authors::Column::Id);
+        select_columns!(ctx, query,
+            "posts" => posts_authors::Column::Id,
+            "imagePlaceholder" | "imageSrcset" => posts_authors::Column::Image);
 
         query
             .filter(posts_authors::Column::Id.eq(self.author_id.unwrap()))
@@ -107,6 +390,70 @@ impl Post {
             .map_err(db_error)
     }
 
+    /// All authors of this post, primary author first.
+    async fn authors(&self, ctx: &Context<'_>) -> Result<Vec<Author>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = PostsAuthors::find().select_only();
+
+        select_columns!(ctx, query, posts_authors::Column);
+        select_columns!(ctx, query,
+            "posts" => posts_authors::Column::Id,
+            "imagePlaceholder" | "imageSrcset" => posts_authors::Column::Image);
+
+        query
+            .filter(posts_pivot_authors_data::Column::PostsId.eq(self.id.deref().unwrap()))
+            .join_rev(
+                JoinType::Join,
+                posts_pivot_authors_data::Relation::Authors.def(),
+            )
+            .order_by(posts_pivot_authors_data::Column::Position, Order::Asc)
+            .order_by(posts_authors::Column::Id, Order::Asc)
+            .into_model::<Author>()
+            .all(db.deref())
+            .await
+            .map_err(db_error)
+    }
+
+    /// Files attached to this post, in display order.
+    async fn attachments(&self, ctx: &Context<'_>) -> Result<Vec<Attachment>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        PostsAttachments::find()
+            .select_only()
+            .column(posts_attachments::Column::Id)
+            .column(posts_attachments::Column::Name)
+            .column(posts_attachments::Column::File)
+            .column(posts_attachments::Column::Size)
+            .column(posts_attachments::Column::MimeType)
+            .column(posts_attachments::Column::DownloadCount)
+            .filter(posts_attachments::Column::PostsId.eq(self.id.deref().unwrap()))
+            .order_by(posts_attachments::Column::Position, Order::Asc)
+            .order_by(posts_attachments::Column::Id, Order::Asc)
+            .into_model::<Attachment>()
+            .all(db.deref())
+            .await
+            .map_err(db_error)
+    }
+
+    /// Number of times this post was viewed. Recent views are counted with a few
+    /// minutes of delay.
+    async fn view_count(&self, ctx: &Context<'_>) -> Result<u64> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        PostsViews::find()
+            .select_only()
+            .column_as(
+                Expr::cust("CAST(COALESCE(SUM(`posts_views`.`views`), 0) AS UNSIGNED)"),
+                "views",
+            )
+            .filter(posts_views::Column::PostsId.eq(self.id.deref().unwrap()))
+            .into_tuple::<u64>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)
+            .map(Option::unwrap_or_default)
+    }
+
     /// Labels/categories associated with this post.
     async fn labels(&self, ctx: &Context<'_>) -> Result<Vec<Label>> {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
@@ -127,6 +474,65 @@ impl Post {
             .await
             .map_err(db_error)
     }
+
+    /// The series this post is part of, if any.
+    async fn series(&self, ctx: &Context<'_>) -> Result<Option<SeriesEntry>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        PostsPivotSeriesData::find()
+            .select_only()
+            .column(posts_pivot_series_data::Column::SeriesId)
+            .column(posts_pivot_series_data::Column::Part)
+            .filter(posts_pivot_series_data::Column::PostsId.eq(self.id.unwrap()))
+            .into_model::<SeriesEntry>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)
+    }
+
+    /// Stored revisions of this post, newest first.
+    #[graphql(guard = "EditorGuard")]
+    async fn revisions(&self, ctx: &Context<'_>) -> Result<Vec<Revision>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let mut query = PostsRevisions::find().select_only();
+
+        select_columns!(ctx, query, posts_revisions::Column);
+
+        query
+            .filter(posts_revisions::Column::PostId.eq(self.id.unwrap()))
+            .order_by(posts_revisions::Column::Id, Order::Desc)
+            .into_model::<Revision>()
+            .all(db.deref())
+            .await
+            .map_err(db_error)
+    }
+
+    /// Difference between two revisions of this post.
+    #[graphql(guard = "EditorGuard")]
+    async fn revision_diff(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The older revision ID.")] from: u32,
+        #[graphql(desc = "The newer revision ID.")] to: u32,
+        #[graphql(default, desc = "Compare by lines or by words.")] granularity: DiffGranularity,
+    ) -> Result<RevisionDiff> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let revision = |id: u32| async move {
+            PostsRevisions::find_by_id(id as i32)
+                .filter(posts_revisions::Column::PostId.eq(self.id.unwrap()))
+                .one(db.deref())
+                .await
+                .map_err(db_error)?
+                .ok_or_else(|| Error::new(format!("Revision {id} not found")))
+        };
+
+        Ok(RevisionDiff::new(
+            &revision(from).await?,
+            &revision(to).await?,
+            granularity,
+        ))
+    }
 }
 
 #[derive(Default)]
@@ -136,7 +542,7 @@ pub struct PostsQuery;
 impl PostsQuery {
     /// Retrieve a paginated list of published posts.
     ///
-    /// Use `featured: true` to filter only featured posts.
+    /// Use `featured: true` to filter only posts that are currently pinned.
     /// Supports cursor-based pagination with `after`, `before`, `first`, and `last` arguments.
     async fn posts(
         &self,
@@ -154,7 +560,7 @@ impl PostsQuery {
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
         let condition = {
             let condition = if featured {
-                Some(posts_data::Column::Featured.eq(true))
+                Some(currently_pinned())
             } else {
                 None
             };
@@ -195,23 +601,25 @@ impl PostsQuery {
     ///
     /// For published posts, only the `id` is required.
     /// For unpublished posts, a valid `token` (preview token) must be provided.
+    /// Translated fields are returned in `locale` (or the `Accept-Language` header),
+    /// falling back to Hungarian.
     async fn post(
         &self,
         ctx: &Context<'_>,
         #[graphql(desc = "The post ID.")] id: u32,
         #[graphql(desc = "Preview token for accessing unpublished posts.")] token: Option<String>,
+        #[graphql(desc = "The preferred locale, e.g. \"en\".")] locale: Option<String>,
     ) -> Result<Option<Post>> {
         ctx.data_unchecked::<IntCounterVec>()
             .with(&labels! {"resource" => "post"})
             .inc();
 
         let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
-        let mut query = PostsData::find().select_only();
+        let mut query = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::Id);
 
-        select_columns!(ctx, query, posts_data::Column);
-        select_columns!(ctx, query,
-            "author" => posts_data::Column::AuthorId,
-            "labels" => posts_data::Column::Id);
+        query = Post::columns(ctx, query);
 
         if let Some(token) = token {
             query = query
@@ -221,12 +629,16 @@ impl PostsQuery {
             query = query.filter(posts_data::Column::Published.eq(true))
         }
 
-        query
+        let mut post = query
             .filter(posts_data::Column::Id.eq(id))
             .order_by(posts_data::Column::Id, Order::Desc)
             .into_model::<Post>()
             .one(db.deref())
             .await
-            .map_err(db_error)
+            .map_err(db_error)?;
+
+        Post::translate(post.as_mut_slice(), db, &Locale::resolve(ctx, locale)).await?;
+
+        Ok(post)
     }
 }
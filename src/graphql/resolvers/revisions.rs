@@ -0,0 +1,191 @@
+use super::Post;
+use crate::{
+    entity::{
+        posts_data::{self, Entity as PostsData},
+        posts_revisions::{self, Entity as PostsRevisions},
+    },
+    graphql::{EditorGuard, types::DateTime},
+    utils::{Maybe, db_error},
+};
+use async_graphql::{Context, Enum, Error, Object, Result, SimpleObject};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    ConnectionTrait, DatabaseTransaction, FromQueryResult, Statement, prelude::*,
+    query::QuerySelect, sea_query::Expr,
+};
+use similar::{ChangeTag, TextDiff};
+use std::{ops::Deref, sync::Arc};
+
+/// A snapshot of a post, recorded every time its title, description or content changes.
+#[derive(SimpleObject, Debug, FromQueryResult)]
+pub struct Revision {
+    /// Unique identifier.
+    pub id: Maybe<u32>,
+    /// Post title at the time of the revision.
+    pub title: Maybe<String>,
+    /// Post description at the time of the revision.
+    pub description: Maybe<Option<String>>,
+    /// Post content at the time of the revision.
+    pub content: Maybe<String>,
+    /// Who made the change, if it was recorded.
+    pub edited_by: Maybe<Option<String>>,
+    /// When the revision was recorded.
+    pub created_at: Maybe<DateTime>,
+}
+
+/// Unit of comparison used when diffing revisions.
+#[derive(Enum, Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DiffGranularity {
+    /// Compare line by line.
+    #[default]
+    Line,
+    /// Compare word by word.
+    Word,
+}
+
+/// Kind of a diff change.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiffTag {
+    /// Text present in both revisions.
+    Equal,
+    /// Text only present in the newer revision.
+    Insert,
+    /// Text only present in the older revision.
+    Delete,
+}
+
+/// A run of text with the same diff tag.
+#[derive(SimpleObject, Debug)]
+pub struct DiffChange {
+    /// Whether the text was kept, inserted or deleted.
+    pub tag: DiffTag,
+    /// The text itself.
+    pub value: String,
+}
+
+/// Field-by-field difference between two revisions.
+#[derive(SimpleObject, Debug)]
+pub struct RevisionDiff {
+    /// Changes in the title.
+    pub title: Vec<DiffChange>,
+    /// Changes in the description.
+    pub description: Vec<DiffChange>,
+    /// Changes in the content.
+    pub content: Vec<DiffChange>,
+}
+
+impl RevisionDiff {
+    pub fn new(
+        old: &posts_revisions::Model,
+        new: &posts_revisions::Model,
+        granularity: DiffGranularity,
+    ) -> Self {
+        let field = |old: &Option<String>, new: &Option<String>| {
+            diff(
+                old.as_deref().unwrap_or_default(),
+                new.as_deref().unwrap_or_default(),
+                granularity,
+            )
+        };
+
+        Self {
+            title: diff(&old.title, &new.title, granularity),
+            description: field(&old.description, &new.description),
+            content: field(&old.content, &new.content),
+        }
+    }
+}
+
+fn diff(old: &str, new: &str, granularity: DiffGranularity) -> Vec<DiffChange> {
+    let diff = match granularity {
+        DiffGranularity::Line => TextDiff::from_lines(old, new),
+        DiffGranularity::Word => TextDiff::from_words(old, new),
+    };
+
+    let mut changes: Vec<DiffChange> = Vec::new();
+
+    for change in diff.iter_all_changes() {
+        let tag = match change.tag() {
+            ChangeTag::Equal => DiffTag::Equal,
+            ChangeTag::Insert => DiffTag::Insert,
+            ChangeTag::Delete => DiffTag::Delete,
+        };
+
+        match changes.last_mut() {
+            Some(last) if last.tag == tag => last.value.push_str(change.value()),
+            _ => changes.push(DiffChange {
+                tag,
+                value: change.value().to_owned(),
+            }),
+        }
+    }
+
+    changes
+}
+
+#[derive(Default)]
+pub struct RevisionsMutation;
+
+#[Object]
+impl RevisionsMutation {
+    /// Restore a post to the state stored in the given revision.
+    ///
+    /// The restore itself is recorded as a new revision.
+    #[graphql(guard = "EditorGuard")]
+    async fn restore_post_revision(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The revision ID to restore.")] id: u32,
+    ) -> Result<Post> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "restore_post_revision"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let revision = PostsRevisions::find_by_id(id as i32)
+            .one(db.deref())
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| Error::new("Revision not found"))?;
+
+        let set_editor = |editor: &str| {
+            Statement::from_string(
+                db.get_database_backend(),
+                format!("SET @revision_editor = {editor}"),
+            )
+        };
+
+        db.execute(set_editor("'api'")).await.map_err(db_error)?;
+
+        PostsData::update_many()
+            .col_expr(posts_data::Column::Title, Expr::value(revision.title))
+            .col_expr(
+                posts_data::Column::Description,
+                Expr::value(revision.description),
+            )
+            .col_expr(posts_data::Column::Content, Expr::value(revision.content))
+            .col_expr(
+                posts_data::Column::UpdatedAt,
+                Expr::current_timestamp().into(),
+            )
+            .filter(posts_data::Column::Id.eq(revision.post_id))
+            .exec(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        db.execute(set_editor("NULL")).await.map_err(db_error)?;
+
+        let mut query = PostsData::find().select_only();
+
+        query = Post::columns(ctx, query);
+
+        query
+            .filter(posts_data::Column::Id.eq(revision.post_id))
+            .into_model::<Post>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| Error::new("Post not found"))
+    }
+}
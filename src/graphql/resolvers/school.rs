@@ -0,0 +1,219 @@
+use crate::{
+    calendar::{self, SchoolCalendar, SchoolDate, SchoolDayKind},
+    entity::{
+        school_calendar_entries::{self, Entity as SchoolCalendarEntries},
+        school_years::{self, Entity as SchoolYears},
+    },
+    graphql::types::Date,
+    select_columns,
+    utils::{Maybe, db_error},
+};
+use async_graphql::{ComplexObject, Context, Object, Result, SimpleObject};
+use chrono::{Local, NaiveDate};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    Condition, DatabaseTransaction, FromQueryResult,
+    prelude::*,
+    query::{QueryOrder, QuerySelect},
+    sea_query::IntoCondition,
+};
+use std::{ops::Deref, sync::Arc};
+
+/// A day in the school calendar.
+#[derive(SimpleObject, Debug)]
+#[graphql(complex)]
+pub struct SchoolDay {
+    /// The date of the day.
+    date: Date,
+    /// Whether there is teaching on this day.
+    is_school_day: bool,
+    /// Why this day is or is not a school day.
+    kind: SchoolDayKind,
+    /// Name of the holiday, break or special day.
+    name: Option<String>,
+    /// Term of the school year, 1 or 2.
+    term: Option<u8>,
+    #[graphql(skip)]
+    school_year_id: Option<i32>,
+}
+
+impl From<SchoolDate> for SchoolDay {
+    fn from(day: SchoolDate) -> Self {
+        Self {
+            date: Date(day.date),
+            is_school_day: day.is_school_day(),
+            kind: day.kind,
+            name: day.name,
+            term: day.term,
+            school_year_id: day.school_year_id,
+        }
+    }
+}
+
+impl SchoolDay {
+    /// The school day on `date`.
+    pub async fn find(db: &DatabaseTransaction, date: NaiveDate) -> Result<Self> {
+        let calendar = SchoolCalendar::load(db, date, date)
+            .await
+            .map_err(db_error)?;
+
+        Ok(calendar.day(date).into())
+    }
+}
+
+#[ComplexObject]
+impl SchoolDay {
+    /// The school year this day is in.
+    async fn school_year(&self, ctx: &Context<'_>) -> Result<Option<SchoolYear>> {
+        let Some(id) = self.school_year_id else {
+            return Ok(None);
+        };
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        find_school_year(ctx, db, school_years::Column::Id.eq(id)).await
+    }
+}
+
+/// A school year with its terms.
+#[derive(SimpleObject, Debug, FromQueryResult)]
+#[graphql(complex)]
+pub struct SchoolYear {
+    /// Unique identifier.
+    id: Maybe<u32>,
+    /// Name of the school year, e.g. "2025/2026".
+    name: Maybe<String>,
+    /// First day of teaching.
+    first_day: Maybe<Date>,
+    /// Last day of the first term.
+    first_term_end: Maybe<Date>,
+    /// Last day of teaching.
+    last_day: Maybe<Date>,
+}
+
+/// A holiday, break, bridge day or working Saturday of a school year.
+#[derive(SimpleObject, Debug)]
+pub struct SchoolCalendarEntry {
+    /// Unique identifier.
+    id: u32,
+    /// Kind of the days.
+    kind: SchoolDayKind,
+    /// Name of the holiday, break or special day.
+    name: Option<String>,
+    /// First day of the entry.
+    first_day: Date,
+    /// Last day of the entry, the same as the first for single days.
+    last_day: Date,
+}
+
+async fn find_school_year(
+    ctx: &Context<'_>,
+    db: &DatabaseTransaction,
+    condition: impl IntoCondition,
+) -> Result<Option<SchoolYear>> {
+    let mut query = SchoolYears::find().select_only();
+
+    select_columns!(ctx, query, school_years::Column);
+    select_columns!(ctx, query, "entries" => school_years::Column::Id);
+
+    query
+        .filter(condition)
+        .order_by_asc(school_years::Column::FirstDay)
+        .into_model::<SchoolYear>()
+        .one(db)
+        .await
+        .map_err(db_error)
+}
+
+#[ComplexObject]
+impl SchoolYear {
+    /// Holidays, breaks, bridge days and working Saturdays of the school year, in date order.
+    async fn entries(&self, ctx: &Context<'_>) -> Result<Vec<SchoolCalendarEntry>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let entries = SchoolCalendarEntries::find()
+            .filter(school_calendar_entries::Column::SchoolYearsId.eq(self.id.unwrap()))
+            .order_by_asc(school_calendar_entries::Column::FirstDay)
+            .all(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        Ok(entries
+            .into_iter()
+            .filter_map(|entry| {
+                let kind = SchoolDayKind::of_entry(&entry.kind)?;
+
+                Some(SchoolCalendarEntry {
+                    id: entry.id as u32,
+                    kind,
+                    name: entry.name,
+                    first_day: Date(entry.first_day),
+                    last_day: Date(entry.last_day),
+                })
+            })
+            .collect())
+    }
+}
+
+#[derive(Default)]
+pub struct SchoolQuery;
+
+#[Object]
+impl SchoolQuery {
+    /// Whether a day is a school day, and why not.
+    async fn school_day(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The date, today if omitted.")] date: Option<Date>,
+    ) -> Result<SchoolDay> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "school_day"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let date = date.map_or_else(|| Local::now().date_naive(), |date| date.0);
+
+        SchoolDay::find(db, date).await
+    }
+
+    /// Retrieve a school year.
+    ///
+    /// Without an ID, the current school year is returned, or the next one during the summer.
+    async fn school_year(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "ID of the school year.")] id: Option<u32>,
+    ) -> Result<Option<SchoolYear>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "school_year"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let condition = match id {
+            Some(id) => Condition::all().add(school_years::Column::Id.eq(id)),
+            None => {
+                Condition::all().add(school_years::Column::LastDay.gte(Local::now().date_naive()))
+            }
+        };
+
+        find_school_year(ctx, db, condition).await
+    }
+
+    /// The first school day after a date, if there is one within a year.
+    async fn next_school_day(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The date to search after, today if omitted.")] after: Option<Date>,
+    ) -> Result<Option<SchoolDay>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "next_school_day"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let after = after.map_or_else(|| Local::now().date_naive(), |date| date.0);
+
+        Ok(calendar::next_school_day(db.deref(), after)
+            .await
+            .map_err(db_error)?
+            .map(SchoolDay::from))
+    }
+}
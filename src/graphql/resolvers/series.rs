@@ -0,0 +1,307 @@
+use super::Post;
+use crate::{
+    entity::{
+        posts_data,
+        posts_pivot_series_data::{self, Entity as PostsPivotSeriesData},
+        posts_series::{self, Entity as PostsSeries},
+    },
+    graphql::Locale,
+    select_columns,
+    utils::{Maybe, db_error},
+};
+use async_graphql::{
+    ComplexObject, Context, Error, Object, OutputType, Result, SimpleObject,
+    connection::{Connection, ConnectionNameType, Edge, EdgeNameType, EmptyFields, query},
+};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    DatabaseTransaction, FromQueryResult, JoinType,
+    prelude::*,
+    query::{Order, QueryOrder, QuerySelect},
+    sea_query::{IntoCondition, SimpleExpr},
+};
+use std::{ops::Deref, sync::Arc};
+
+/// A series of posts published in multiple parts.
+#[derive(SimpleObject, Debug, FromQueryResult)]
+#[graphql(complex)]
+pub struct Series {
+    /// Unique identifier.
+    pub id: Maybe<u32>,
+    /// Series title.
+    pub title: Maybe<String>,
+    /// URL slug of the series.
+    pub slug: Maybe<String>,
+    /// Short description of the series.
+    pub description: Maybe<Option<String>>,
+}
+
+/// Position of a post inside a series.
+#[derive(SimpleObject)]
+pub struct SeriesPart {
+    /// Part number of the post in the series.
+    pub part: u32,
+}
+
+/// The series a post belongs to, with links to its neighbouring parts.
+#[derive(SimpleObject, Debug, FromQueryResult)]
+#[graphql(complex)]
+pub struct SeriesEntry {
+    #[graphql(skip)]
+    pub series_id: u32,
+    /// Part number of the post in the series.
+    pub part: u32,
+}
+
+pub struct SeriesPostConnectionName;
+
+impl ConnectionNameType for SeriesPostConnectionName {
+    fn type_name<T: OutputType>() -> String {
+        "SeriesPostConnection".to_owned()
+    }
+}
+
+pub struct SeriesPostEdgeName;
+
+impl EdgeNameType for SeriesPostEdgeName {
+    fn type_name<T: OutputType>() -> String {
+        "SeriesPostEdge".to_owned()
+    }
+}
+
+/// Parts of a series, keyed by their part number.
+pub type SeriesPostConnection =
+    Connection<usize, Post, EmptyFields, SeriesPart, SeriesPostConnectionName, SeriesPostEdgeName>;
+
+#[derive(Debug, FromQueryResult)]
+struct PartRow {
+    posts_id: u32,
+    part: u32,
+}
+
+fn find_published_part(series_id: u32) -> Select<PostsPivotSeriesData> {
+    PostsPivotSeriesData::find()
+        .select_only()
+        .column(posts_pivot_series_data::Column::PostsId)
+        .column(posts_pivot_series_data::Column::Part)
+        .join(
+            JoinType::Join,
+            posts_pivot_series_data::Relation::Posts.def(),
+        )
+        .filter(posts_pivot_series_data::Column::SeriesId.eq(series_id))
+        .filter(posts_data::Column::Published.eq(true))
+}
+
+async fn find_series(
+    ctx: &Context<'_>,
+    db: &DatabaseTransaction,
+    condition: impl IntoCondition,
+) -> Result<Option<Series>> {
+    let mut query = PostsSeries::find().select_only();
+
+    select_columns!(ctx, query, posts_series::Column);
+    select_columns!(ctx, query, "posts" => posts_series::Column::Id);
+
+    query
+        .filter(condition)
+        .into_model::<Series>()
+        .one(db)
+        .await
+        .map_err(db_error)
+}
+
+#[ComplexObject]
+impl Series {
+    /// Published parts of the series, ordered by part number.
+    ///
+    /// The cursor of each edge is its part number.
+    async fn posts(
+        &self,
+        ctx: &Context<'_>,
+        after: Option<String>,
+        before: Option<String>,
+        first: Option<i32>,
+        last: Option<i32>,
+    ) -> Result<SeriesPostConnection> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let series_id = self.id.unwrap();
+
+        query(
+            after,
+            before,
+            first,
+            last,
+            |after: Option<usize>, before: Option<usize>, first, last| async move {
+                let mut parts = find_published_part(series_id);
+
+                if let Some(after) = after {
+                    parts = parts.filter(posts_pivot_series_data::Column::Part.gt(after as u32));
+                }
+
+                if let Some(before) = before {
+                    parts = parts.filter(posts_pivot_series_data::Column::Part.lt(before as u32));
+                }
+
+                let limit = first.or(last);
+                let order = if first.is_none() && last.is_some() {
+                    Order::Desc
+                } else {
+                    Order::Asc
+                };
+
+                if let Some(limit) = limit {
+                    parts = parts.limit(limit as u64 + 1);
+                }
+
+                let mut parts = parts
+                    .order_by(posts_pivot_series_data::Column::Part, order.clone())
+                    .into_model::<PartRow>()
+                    .all(db.deref())
+                    .await
+                    .map_err(db_error)?;
+
+                let has_more = limit.is_some_and(|limit| parts.len() > limit);
+                parts.truncate(limit.unwrap_or(parts.len()));
+
+                let mut connection = if order == Order::Desc {
+                    parts.reverse();
+                    Connection::new(has_more, before.is_some())
+                } else {
+                    Connection::new(after.is_some(), has_more)
+                };
+
+                let mut query = posts_data::Entity::find()
+                    .select_only()
+                    .column(posts_data::Column::Id);
+
+                query = Post::connection_columns(ctx, query);
+
+                let mut posts = query
+                    .filter(posts_data::Column::Id.is_in(parts.iter().map(|part| part.posts_id)))
+                    .into_model::<Post>()
+                    .all(db.deref())
+                    .await
+                    .map_err(db_error)?;
+
+                Post::translate(&mut posts, db, &Locale::resolve(ctx, None)).await?;
+
+                connection
+                    .edges
+                    .extend(parts.into_iter().filter_map(|part| {
+                        let index = posts
+                            .iter()
+                            .position(|post| *post.id == Some(part.posts_id))?;
+                        let post = posts.swap_remove(index);
+
+                        Some(Edge::with_additional_fields(
+                            part.part as usize,
+                            post,
+                            SeriesPart { part: part.part },
+                        ))
+                    }));
+
+                Ok::<_, Error>(connection)
+            },
+        )
+        .await
+    }
+}
+
+impl SeriesEntry {
+    async fn neighbour(
+        &self,
+        ctx: &Context<'_>,
+        condition: SimpleExpr,
+        order: Order,
+    ) -> Result<Option<Post>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let Some(part) = find_published_part(self.series_id)
+            .filter(condition)
+            .order_by(posts_pivot_series_data::Column::Part, order)
+            .into_model::<PartRow>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)?
+        else {
+            return Ok(None);
+        };
+
+        let mut query = posts_data::Entity::find()
+            .select_only()
+            .column(posts_data::Column::Id);
+
+        query = Post::columns(ctx, query);
+
+        let mut post = query
+            .filter(posts_data::Column::Id.eq(part.posts_id))
+            .into_model::<Post>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        Post::translate(post.as_mut_slice(), db, &Locale::resolve(ctx, None)).await?;
+
+        Ok(post)
+    }
+}
+
+#[ComplexObject]
+impl SeriesEntry {
+    /// The series itself.
+    async fn series(&self, ctx: &Context<'_>) -> Result<Option<Series>> {
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        find_series(ctx, db, posts_series::Column::Id.eq(self.series_id)).await
+    }
+
+    /// The previous published part, if any.
+    async fn previous(&self, ctx: &Context<'_>) -> Result<Option<Post>> {
+        self.neighbour(
+            ctx,
+            posts_pivot_series_data::Column::Part.lt(self.part),
+            Order::Desc,
+        )
+        .await
+    }
+
+    /// The next published part, if any.
+    async fn next(&self, ctx: &Context<'_>) -> Result<Option<Post>> {
+        self.neighbour(
+            ctx,
+            posts_pivot_series_data::Column::Part.gt(self.part),
+            Order::Asc,
+        )
+        .await
+    }
+}
+
+#[derive(Default)]
+pub struct SeriesQuery;
+
+#[Object]
+impl SeriesQuery {
+    /// Retrieve a series by its ID or URL slug.
+    ///
+    /// Exactly one of `id` and `slug` must be provided.
+    async fn series(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The series' unique identifier.")] id: Option<u32>,
+        #[graphql(desc = "The series' URL slug.")] slug: Option<String>,
+    ) -> Result<Option<Series>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "series"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let condition = match (id, slug) {
+            (Some(id), None) => posts_series::Column::Id.eq(id),
+            (None, Some(slug)) => posts_series::Column::Slug.eq(slug),
+            _ => return Err(Error::new("Exactly one of id or slug must be provided")),
+        };
+
+        find_series(ctx, db, condition).await
+    }
+}
@@ -0,0 +1,105 @@
+use super::Image;
+use crate::{
+    Config,
+    graphql::EditorGuard,
+    media::{ImageMeta, Storage, UploadError, store_upload},
+};
+use async_graphql::{Context, Enum, Error, Object, Result, Upload};
+use prometheus::{IntCounterVec, labels};
+use std::{io::Read, sync::Arc};
+
+/// What an image is used for, deciding the storage directory it is uploaded to.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageKind {
+    /// Post cover and gallery images, stored in `posts_images`.
+    Posts,
+    /// Standalone album images, stored in `albums_images`.
+    Albums,
+    /// Author profile images, stored in `authors_images`.
+    Authors,
+    /// Colleague profile images, stored in `colleagues_images`.
+    Colleagues,
+}
+
+impl ImageKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::Albums => "albums",
+            Self::Authors => "authors",
+            Self::Colleagues => "colleagues",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct UploadsMutation;
+
+#[Object]
+impl UploadsMutation {
+    /// Upload an image using a GraphQL multipart request.
+    ///
+    /// JPEG, PNG, GIF and WebP images are accepted. EXIF data is stripped, and the file is
+    /// stored under a name derived from its contents. The returned `file` can be stored
+    /// as a post cover, gallery image or profile image.
+    #[graphql(guard = "EditorGuard")]
+    async fn upload_image(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "What the image is used for.")] kind: ImageKind,
+        #[graphql(desc = "The image file.")] file: Upload,
+    ) -> Result<Image> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "upload_image"})
+            .inc();
+
+        let config = ctx.data_unchecked::<Config>();
+
+        let Some(storage) = ctx.data_opt::<Arc<dyn Storage>>() else {
+            return Err(Error::new("Uploads are not available"));
+        };
+
+        // Read one byte past the limit so oversized files are detected without reading them whole.
+        // Uploads are buffered in temporary files, so they are read in a blocking task.
+        let upload = file.value(ctx)?;
+        let max_size = config.upload_max_size;
+        let original = tokio::task::spawn_blocking(move || {
+            let mut original = Vec::new();
+
+            upload
+                .into_read()
+                .take(max_size + 1)
+                .read_to_end(&mut original)
+                .map(|_| original)
+        })
+        .await
+        .expect("Upload read task panicked")?;
+
+        let stored = store_upload(
+            storage.as_ref(),
+            kind.as_str(),
+            original,
+            config.upload_max_size,
+        )
+        .await
+        .map_err(|err| match err {
+            UploadError::Storage(err) => {
+                tracing::error!("Could not store uploaded image: {err}");
+                Error::new("Could not store image")
+            }
+            err => Error::new(err.to_string()),
+        })?;
+
+        Ok(Image::new(
+            config,
+            kind.as_str(),
+            &stored.file,
+            ImageMeta {
+                width: Some(stored.width),
+                height: Some(stored.height),
+                mime_type: Some(stored.mime_type.to_owned()),
+                ..Default::default()
+            },
+        ))
+    }
+}
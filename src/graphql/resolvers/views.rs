@@ -0,0 +1,132 @@
+use super::Post;
+use crate::{
+    entity::{
+        posts_data::{self, Entity as PostsData},
+        posts_views,
+    },
+    graphql::Locale,
+    utils::db_error,
+    views::ViewCounter,
+};
+use async_graphql::{Context, Enum, Error, Object, Result};
+use chrono::{Days, Local};
+use prometheus::{IntCounterVec, labels};
+use sea_orm::{
+    DatabaseTransaction, JoinType,
+    prelude::*,
+    query::{Order, QueryOrder, QuerySelect},
+    sea_query::Expr,
+};
+use std::{ops::Deref, sync::Arc};
+
+const MAX_POPULAR_POSTS: u32 = 50;
+
+/// Time window for counting views.
+#[derive(Enum, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PopularityPeriod {
+    /// Today.
+    Day,
+    /// The last 7 days, including today.
+    Week,
+    /// The last 30 days, including today.
+    Month,
+}
+
+impl PopularityPeriod {
+    fn days(self) -> u64 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+            Self::Month => 30,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ViewsQuery;
+
+#[Object]
+impl ViewsQuery {
+    /// The most viewed published posts in a time window, most viewed first.
+    async fn popular_posts(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The time window to count views in.")] period: PopularityPeriod,
+        #[graphql(default = 10, desc = "The maximum number of posts, at most 50.")] limit: u32,
+    ) -> Result<Vec<Post>> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "popular_posts"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+        let since = Local::now().date_naive() - Days::new(period.days() - 1);
+        let mut query = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::Id);
+
+        query = Post::columns(ctx, query);
+
+        let mut posts = query
+            .join_rev(JoinType::Join, posts_views::Relation::Posts.def())
+            .filter(posts_views::Column::Date.gte(since))
+            .filter(posts_data::Column::Published.eq(true))
+            .group_by(posts_data::Column::Id)
+            .order_by(Expr::cust("SUM(`posts_views`.`views`)"), Order::Desc)
+            .order_by(posts_data::Column::Id, Order::Desc)
+            .limit(u64::from(limit.min(MAX_POPULAR_POSTS)))
+            .into_model::<Post>()
+            .all(db.deref())
+            .await
+            .map_err(db_error)?;
+
+        Post::translate(&mut posts, db, &Locale::resolve(ctx, None)).await?;
+
+        Ok(posts)
+    }
+}
+
+#[derive(Default)]
+pub struct ViewsMutation;
+
+#[Object]
+impl ViewsMutation {
+    /// Count a view of a published post.
+    ///
+    /// Nothing is stored about the reader, only the number of views per post and day.
+    async fn record_view(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(desc = "The viewed post ID.")] post_id: u32,
+    ) -> Result<bool> {
+        ctx.data_unchecked::<IntCounterVec>()
+            .with(&labels! {"resource" => "record_view"})
+            .inc();
+
+        let db = ctx.data::<Arc<DatabaseTransaction>>().unwrap();
+
+        let exists = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::Id)
+            .filter(posts_data::Column::Id.eq(post_id))
+            .filter(posts_data::Column::Published.eq(true))
+            .into_tuple::<u32>()
+            .one(db.deref())
+            .await
+            .map_err(db_error)?
+            .is_some();
+
+        if !exists {
+            return Err(Error::new("Post not found"));
+        }
+
+        ctx.data_unchecked::<ViewCounter>()
+            .record(post_id)
+            .await
+            .map_err(|err| {
+                tracing::error!("Could not record view: {err}");
+                Error::new("Could not record view")
+            })?;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,76 @@
+use crate::graphql::resolvers::AlbumKind;
+use async_graphql::types::connection::CursorType;
+use chrono::NaiveDateTime;
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Position of an album in the list of all albums, ordered by date, kind and ID.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AlbumCursor {
+    date: NaiveDateTime,
+    kind: AlbumKind,
+    id: u32,
+}
+
+impl AlbumCursor {
+    pub fn new(date: NaiveDateTime, kind: AlbumKind, id: u32) -> Self {
+        Self { date, kind, id }
+    }
+
+    pub fn date(&self) -> NaiveDateTime {
+        self.date
+    }
+
+    pub fn kind(&self) -> AlbumKind {
+        self.kind
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl CursorType for AlbumCursor {
+    type Error = AlbumCursorError;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let mut parts = s.split('#');
+
+        let (Some(date), Some(kind), Some(id), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AlbumCursorError::WrongFormat);
+        };
+
+        let kind = match kind {
+            "post" => AlbumKind::Post,
+            "album" => AlbumKind::Standalone,
+            _ => return Err(AlbumCursorError::WrongFormat),
+        };
+
+        Ok(Self {
+            date: NaiveDateTime::parse_from_str(date, DATE_TIME_FORMAT)?,
+            kind,
+            id: id.parse()?,
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        let kind = match self.kind {
+            AlbumKind::Post => "post",
+            AlbumKind::Standalone => "album",
+        };
+
+        format!("{}#{kind}#{}", self.date.format(DATE_TIME_FORMAT), self.id)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum AlbumCursorError {
+    #[error("Wrong date format in cursor")]
+    WrongDate(#[from] chrono::ParseError),
+    #[error("Wrong cursor format")]
+    WrongFormat,
+    #[error("Invalid id in cursor")]
+    InvalidId(#[from] std::num::ParseIntError),
+}
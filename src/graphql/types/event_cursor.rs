@@ -0,0 +1,54 @@
+use async_graphql::types::connection::CursorType;
+use chrono::NaiveDateTime;
+
+const DATE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Position of an event in a list of events, ordered by start and ID.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EventCursor {
+    date: NaiveDateTime,
+    id: u32,
+}
+
+impl EventCursor {
+    pub fn new(date: NaiveDateTime, id: u32) -> Self {
+        Self { date, id }
+    }
+
+    pub fn date(&self) -> NaiveDateTime {
+        self.date
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+impl CursorType for EventCursor {
+    type Error = EventCursorError;
+
+    fn decode_cursor(s: &str) -> Result<Self, Self::Error> {
+        let Some((date, id)) = s.split_once('#') else {
+            return Err(EventCursorError::WrongFormat);
+        };
+
+        Ok(Self {
+            date: NaiveDateTime::parse_from_str(date, DATE_TIME_FORMAT)?,
+            id: id.parse()?,
+        })
+    }
+
+    fn encode_cursor(&self) -> String {
+        format!("{}#{}", self.date.format(DATE_TIME_FORMAT), self.id)
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum EventCursorError {
+    #[error("Wrong date format in cursor")]
+    WrongDate(#[from] chrono::ParseError),
+    #[error("Wrong cursor format")]
+    WrongFormat,
+    #[error("Invalid id in cursor")]
+    InvalidId(#[from] std::num::ParseIntError),
+}
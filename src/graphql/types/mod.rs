@@ -1,7 +1,11 @@
+mod album_cursor;
 mod date;
 mod datetime;
+mod event_cursor;
 mod post_cursor;
 
+pub use album_cursor::*;
 pub use date::*;
 pub use datetime::*;
+pub use event_cursor::*;
 pub use post_cursor::*;
@@ -1,21 +1,74 @@
-use crate::AppState;
+use crate::{
+    AppState, Config,
+    calendar::{FeedOptions, events_feed},
+    graphql::{Editor, Locale},
+    media::{DownloadError, ResizeError, ResizeOptions, download_attachment},
+};
 use async_graphql::{Response, ServerError, http::GraphiQLSource};
 use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
 use axum::{
     Router,
-    extract::State,
-    http::StatusCode,
-    response::Html,
+    body::Body,
+    extract::{Path, Query, State},
+    http::{
+        HeaderMap, StatusCode,
+        header::{
+            ACCEPT_LANGUAGE, AUTHORIZATION, CACHE_CONTROL, CONTENT_DISPOSITION, CONTENT_TYPE,
+        },
+    },
+    response::{Html, IntoResponse, Response as HttpResponse},
     routing::{get, post},
 };
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use prometheus::TextEncoder;
 use sea_orm::TransactionTrait;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 
 const GRAPHQL_PATH: &str = "/graphql";
 
-async fn graphql(State(state): State<AppState>, request: GraphQLRequest) -> GraphQLResponse {
-    let request = request.into_inner();
+fn is_editor(headers: &HeaderMap, config: &Config) -> bool {
+    let Some(ref token) = config.editor_token else {
+        return false;
+    };
+
+    let Some(given) = headers
+        .get(AUTHORIZATION)
+        .and_then(|value| value.as_bytes().strip_prefix(b"Bearer "))
+    else {
+        return false;
+    };
+
+    // Digests are compared so that neither the contents nor the length of the token leak
+    // through timing.
+    Sha256::digest(given)
+        .ct_eq(&Sha256::digest(token.as_bytes()))
+        .into()
+}
+
+async fn graphql(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    let mut request = request.into_inner();
+
+    if is_editor(&headers, &state.config) {
+        request = request.data(Editor);
+    }
+
+    if let Some(storage) = state.storage {
+        request = request.data(storage);
+    }
+
+    if let Some(locale) = headers
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(Locale::from_accept_language)
+    {
+        request = request.data(locale);
+    }
 
     if request.operation_name == Some("IntrospectionQuery".into()) {
         return state.schema.execute(request).await.into();
@@ -67,10 +120,106 @@ async fn metrics(State(state): State<AppState>) -> Result<String, StatusCode> {
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+async fn image(
+    State(state): State<AppState>,
+    Path((kind, file)): Path<(String, String)>,
+    Query(options): Query<ResizeOptions>,
+) -> Result<HttpResponse, StatusCode> {
+    let Some(resizer) = state.image_resizer else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    match resizer.variant(&kind, &file, &options).await {
+        Ok((image, format)) => Ok((
+            [
+                (CONTENT_TYPE, format.mime_type()),
+                (CACHE_CONTROL, "public, max-age=31536000, immutable"),
+            ],
+            image,
+        )
+            .into_response()),
+        Err(ResizeError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(ResizeError::SizeNotAllowed) => Err(StatusCode::BAD_REQUEST),
+        Err(err) => {
+            tracing::error!("Could not resize image {kind}/{file}: {err}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// `Content-Disposition` header saving a download as `name`.
+///
+/// Old clients get an ASCII-only fallback name, others the UTF-8 name from `filename*`.
+fn content_disposition(name: &str) -> String {
+    let fallback: String = name
+        .chars()
+        .map(|c| {
+            if c == ' ' || (c.is_ascii_graphic() && !matches!(c, '"' | '\\')) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+
+    format!(
+        "attachment; filename=\"{fallback}\"; filename*=UTF-8''{}",
+        utf8_percent_encode(name, NON_ALPHANUMERIC)
+    )
+}
+
+async fn download(
+    State(state): State<AppState>,
+    Path(id): Path<u32>,
+) -> Result<HttpResponse, StatusCode> {
+    let Some(storage) = state.storage else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    match download_attachment(&state.database, storage.as_ref(), id).await {
+        Ok(download) => Ok((
+            [
+                (CONTENT_TYPE, download.mime_type),
+                (CONTENT_DISPOSITION, content_disposition(&download.name)),
+            ],
+            Body::from_stream(download.contents),
+        )
+            .into_response()),
+        Err(DownloadError::NotFound) => Err(StatusCode::NOT_FOUND),
+        Err(err) => {
+            tracing::error!("Could not download attachment {id}: {err}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn calendar(
+    State(state): State<AppState>,
+    Query(options): Query<FeedOptions>,
+) -> Result<HttpResponse, StatusCode> {
+    match events_feed(&state.database, &state.config, &options).await {
+        Ok(feed) => Ok((
+            [
+                (CONTENT_TYPE, "text/calendar; charset=utf-8"),
+                (CACHE_CONTROL, "public, max-age=3600"),
+            ],
+            feed,
+        )
+            .into_response()),
+        Err(err) => {
+            tracing::error!("Could not render calendar feed: {err}");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route(GRAPHQL_PATH, get(graphiql))
         .route(GRAPHQL_PATH, post(graphql))
+        .route("/img/{kind}/{file}", get(image))
+        .route("/download/{id}", get(download))
+        .route("/calendar/events.ics", get(calendar))
         .route("/metrics", get(metrics))
         .route("/readiness", get(|| async {}))
         .route("/liveness", get(|| async {}))
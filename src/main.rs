@@ -1,10 +1,20 @@
+mod calendar;
+mod content;
 mod database;
 mod entity;
 mod graphql;
 mod http;
+mod media;
 mod utils;
-
-use crate::{graphql::create_schema, utils::SignalHandler};
+mod views;
+
+use crate::{
+    calendar::{ImportOptions, import_events},
+    graphql::create_schema,
+    media::{ImageResizer, PlaceholderJob, Storage, open_storage},
+    utils::SignalHandler,
+    views::{ViewCounter, ViewFlushJob},
+};
 use axum::Router;
 use envconfig::Envconfig;
 use graphql::Schema;
@@ -13,6 +23,7 @@ use sea_orm::DatabaseConnection;
 use std::{
     error::Error,
     net::{IpAddr, SocketAddr},
+    sync::Arc,
 };
 use tokio::net::TcpListener;
 use tower::ServiceBuilder;
@@ -32,6 +43,45 @@ struct Config {
     pub redis_url: String,
     #[envconfig(from = "STORAGE_BASE_URL")]
     pub storage_base_url: String,
+    #[envconfig(from = "EDITOR_TOKEN")]
+    pub editor_token: Option<String>,
+    #[envconfig(from = "SANITIZE_TAGS")]
+    pub sanitize_tags: Option<String>,
+    #[envconfig(from = "SANITIZE_ATTRIBUTES")]
+    pub sanitize_attributes: Option<String>,
+    #[envconfig(from = "SANITIZE_URL_SCHEMES")]
+    pub sanitize_url_schemes: Option<String>,
+    #[envconfig(
+        from = "EMBED_HOSTS",
+        default = "www.youtube.com/embed/,www.youtube-nocookie.com/embed/,www.google.com/maps/embed"
+    )]
+    pub embed_hosts: String,
+    #[envconfig(from = "CONTENT_ASSET_PATHS", default = "storage/=,uploads/=uploads/")]
+    pub content_asset_paths: String,
+    #[envconfig(from = "CONTENT_LAZY_IMAGES", default = "true")]
+    pub content_lazy_images: bool,
+    #[envconfig(from = "CONTENT_EXTERNAL_LINK_REL", default = "true")]
+    pub content_external_link_rel: bool,
+    #[envconfig(from = "CONTENT_CACHE_SIZE", default = "1000")]
+    pub content_cache_size: usize,
+    #[envconfig(from = "PUBLIC_BASE_URL", default = "")]
+    pub public_base_url: String,
+    #[envconfig(from = "STORAGE_URL")]
+    pub storage_url: Option<String>,
+    #[envconfig(from = "IMAGE_CACHE_DIR", default = "/tmp/image-cache")]
+    pub image_cache_dir: String,
+    #[envconfig(from = "IMAGE_SIZES", default = "160,320,640,960,1280,1920")]
+    pub image_sizes: String,
+    #[envconfig(from = "IMAGE_PLACEHOLDER_INTERVAL", default = "3600")]
+    pub image_placeholder_interval: u64,
+    #[envconfig(from = "UPLOAD_MAX_SIZE", default = "10485760")]
+    pub upload_max_size: u64,
+    #[envconfig(from = "VIEW_FLUSH_INTERVAL", default = "300")]
+    pub view_flush_interval: u64,
+    #[envconfig(from = "CALENDAR_PAST_DAYS", default = "180")]
+    pub calendar_past_days: u32,
+    #[envconfig(from = "CALENDAR_FUTURE_DAYS", default = "365")]
+    pub calendar_future_days: u32,
 }
 
 fn init_logger() {
@@ -63,6 +113,8 @@ struct AppState {
     pub config: Config,
     pub counter: IntCounterVec,
     pub prometheus_registry: Registry,
+    pub storage: Option<Arc<dyn Storage>>,
+    pub image_resizer: Option<Arc<ImageResizer>>,
 }
 
 #[tokio::main]
@@ -70,6 +122,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
     init_logger();
 
     let config = Config::init_from_env()?;
+    let mut args = std::env::args().skip(1);
+
+    if let Some(command) = args.next() {
+        match command.as_str() {
+            "import-events" => {
+                let options = ImportOptions::from_args(args)?;
+                let database = database::connect(&config.database_url).await;
+
+                import_events(&database, &options).await?;
+
+                return Ok(());
+            }
+            _ => return Err(format!("Unknown command: {command}").into()),
+        }
+    }
 
     let counter = IntCounterVec::new(
         Opts::new("query_req_count", "count of resource queries"),
@@ -83,8 +150,21 @@ async fn main() -> Result<(), Box<dyn Error>> {
         .register(Box::new(counter.clone()))
         .expect("Could not register counter to Prometheus registry");
 
-    let schema = create_schema(&config).await;
     let database = database::connect(&config.database_url).await;
+    let views = ViewCounter::new(&config.redis_url).await?;
+
+    ViewFlushJob::new(&config, views.clone(), database.clone()).spawn();
+
+    let schema = create_schema(&config, views).await;
+
+    let storage = open_storage(&config)?;
+    let image_resizer = storage
+        .clone()
+        .map(|storage| Arc::new(ImageResizer::new(&config, storage)));
+
+    if let Some(ref storage) = storage {
+        PlaceholderJob::new(&config, database.clone(), Arc::clone(storage)).spawn();
+    }
 
     let socket_addr = SocketAddr::new(config.bind_addr, config.bind_port);
 
@@ -94,6 +174,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
         config,
         counter,
         prometheus_registry,
+        storage,
+        image_resizer,
     };
 
     let app = http::routes().with_state(state);
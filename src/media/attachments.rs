@@ -0,0 +1,62 @@
+use super::{Storage, StorageError, StorageStream};
+use crate::entity::{
+    posts_attachments::{self, Entity as PostsAttachments},
+    posts_data,
+};
+use sea_orm::{
+    DatabaseConnection, DbErr, JoinType, prelude::*, query::QuerySelect, sea_query::Expr,
+};
+
+/// Storage directory of post attachments.
+pub const ATTACHMENTS_DIRECTORY: &str = "posts_attachments";
+
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    #[error("Attachment not found")]
+    NotFound,
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+/// An attachment opened for download.
+pub struct Download {
+    pub name: String,
+    pub mime_type: String,
+    pub contents: StorageStream,
+}
+
+/// Open an attachment of a published post for download, and count the download.
+pub async fn download_attachment(
+    db: &DatabaseConnection,
+    storage: &dyn Storage,
+    id: u32,
+) -> Result<Download, DownloadError> {
+    let attachment = PostsAttachments::find_by_id(id as i32)
+        .join(JoinType::Join, posts_attachments::Relation::Posts.def())
+        .filter(posts_data::Column::Published.eq(true))
+        .one(db)
+        .await?
+        .ok_or(DownloadError::NotFound)?;
+
+    let contents = storage
+        .stream(ATTACHMENTS_DIRECTORY, &attachment.file)
+        .await?
+        .ok_or(DownloadError::NotFound)?;
+
+    PostsAttachments::update_many()
+        .col_expr(
+            posts_attachments::Column::DownloadCount,
+            Expr::col(posts_attachments::Column::DownloadCount).add(1),
+        )
+        .filter(posts_attachments::Column::Id.eq(attachment.id))
+        .exec(db)
+        .await?;
+
+    Ok(Download {
+        name: attachment.name,
+        mime_type: attachment.mime_type,
+        contents,
+    })
+}
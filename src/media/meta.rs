@@ -0,0 +1,47 @@
+use serde::Deserialize;
+use serde_json::Value as Json;
+
+/// Metadata stored alongside an image file name.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ImageMeta {
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub mime_type: Option<String>,
+}
+
+impl ImageMeta {
+    /// Parse metadata stored as JSON, ignoring anything malformed.
+    pub fn from_json(json: Option<&Json>) -> Self {
+        json.and_then(|json| Self::deserialize(json).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Parse a gallery stored as JSON into file names and their metadata.
+///
+/// Accepts the normalised form (an array of `{"file": ..., "alt": ..., ...}`
+/// objects) as well as the legacy forms: an array or object of file names.
+pub fn gallery_entries(json: &Json) -> Option<Vec<(&str, ImageMeta)>> {
+    let entries: Vec<&Json> = match json {
+        Json::Array(entries) => entries.iter().collect(),
+        Json::Object(entries) => entries.values().collect(),
+        _ => return None,
+    };
+
+    Some(
+        entries
+            .into_iter()
+            .filter_map(|entry| match entry {
+                Json::String(file) => Some((file.as_str(), ImageMeta::default())),
+                Json::Object(object) => Some((
+                    object.get("file")?.as_str()?,
+                    ImageMeta::from_json(Some(entry)),
+                )),
+                _ => None,
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,15 @@
+mod attachments;
+mod meta;
+mod placeholders;
+mod resize;
+mod srcset;
+mod storage;
+mod upload;
+
+pub use attachments::*;
+pub use meta::*;
+pub use placeholders::*;
+pub use resize::*;
+pub use srcset::*;
+pub use storage::*;
+pub use upload::*;
@@ -0,0 +1,284 @@
+use super::{Storage, StorageError, gallery_entries, image_directory};
+use crate::{
+    Config,
+    entity::{
+        albums::{self, Entity as Albums},
+        colleagues_data::{self, Entity as ColleaguesData},
+        image_placeholders::{self, Entity as ImagePlaceholders},
+        posts_authors::{self, Entity as PostsAuthors},
+        posts_data::{self, Entity as PostsData},
+    },
+};
+use chrono::{Local, Timelike};
+use image::{ImageError, RgbaImage};
+use sea_orm::{
+    DatabaseConnection, DbErr, Set, prelude::*, query::QuerySelect, sea_query::OnConflict,
+};
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::Arc,
+    time::Duration,
+};
+
+const BLURHASH_COMPONENTS_X: u32 = 4;
+const BLURHASH_COMPONENTS_Y: u32 = 3;
+/// Images are scaled down to fit this size before computing their placeholders.
+const THUMBNAIL_SIZE: u32 = 64;
+
+#[derive(thiserror::Error, Debug)]
+enum PlaceholderError {
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Image error: {0}")]
+    Image(#[from] ImageError),
+    #[error("Blurhash error: {0}")]
+    Blurhash(#[from] blurhash::Error),
+}
+
+/// The most common colour of `image`, as `#rrggbb`.
+///
+/// Opaque pixels are grouped into buckets of similar colours, and the average
+/// of the largest bucket is returned.
+fn dominant_color(image: &RgbaImage) -> String {
+    let mut buckets = vec![(0u32, [0u32; 3]); 1 << 12];
+
+    for pixel in image.pixels().filter(|pixel| pixel[3] >= 128) {
+        let [r, g, b, _] = pixel.0;
+        let key = (usize::from(r >> 4) << 8) | (usize::from(g >> 4) << 4) | usize::from(b >> 4);
+        let (count, sum) = &mut buckets[key];
+
+        *count += 1;
+        sum[0] += u32::from(r);
+        sum[1] += u32::from(g);
+        sum[2] += u32::from(b);
+    }
+
+    match buckets.iter().max_by_key(|(count, _)| *count) {
+        Some((count, [r, g, b])) if *count > 0 => {
+            format!("#{:02x}{:02x}{:02x}", r / count, g / count, b / count)
+        }
+        _ => "#000000".to_owned(),
+    }
+}
+
+fn compute(original: &[u8]) -> Result<(String, String), PlaceholderError> {
+    let image = image::load_from_memory(original)?
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .to_rgba8();
+
+    let blurhash = blurhash::encode(
+        BLURHASH_COMPONENTS_X,
+        BLURHASH_COMPONENTS_Y,
+        image.width(),
+        image.height(),
+        image.as_raw(),
+    )?;
+
+    Ok((blurhash, dominant_color(&image)))
+}
+
+/// Background job that keeps the `image_placeholders` table up to date.
+///
+/// Every run collects the images referenced by posts, albums, authors and colleagues, and
+/// computes placeholders for the ones that are new or modified since the last run.
+pub struct PlaceholderJob {
+    db: DatabaseConnection,
+    storage: Arc<dyn Storage>,
+    interval: Duration,
+    /// Modification times of the images whose placeholders could not be computed, which are
+    /// not retried until they change.
+    failed: HashMap<(&'static str, String), DateTime>,
+}
+
+impl PlaceholderJob {
+    pub fn new(config: &Config, db: DatabaseConnection, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            db,
+            storage,
+            interval: Duration::from_secs(config.image_placeholder_interval),
+            failed: HashMap::new(),
+        }
+    }
+
+    pub fn spawn(mut self) {
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = self.run().await {
+                    tracing::error!("Could not update image placeholders: {err}");
+                }
+
+                tokio::time::sleep(self.interval).await;
+            }
+        });
+    }
+
+    /// Storage directories and file names of all referenced images.
+    async fn referenced_images(&self) -> Result<BTreeSet<(&'static str, String)>, DbErr> {
+        let mut images = BTreeSet::new();
+
+        let posts_images = image_directory("posts").unwrap();
+        let posts = PostsData::find()
+            .select_only()
+            .column(posts_data::Column::IndexImage)
+            .column(posts_data::Column::Images)
+            .into_tuple::<(Option<String>, Json)>()
+            .all(&self.db)
+            .await?;
+
+        for (index_image, gallery) in posts {
+            images.extend(index_image.map(|file| (posts_images, file)));
+            images.extend(
+                gallery_entries(&gallery)
+                    .into_iter()
+                    .flatten()
+                    .map(|(file, _)| (posts_images, file.to_owned())),
+            );
+        }
+
+        let albums_images = image_directory("albums").unwrap();
+        let albums = Albums::find()
+            .select_only()
+            .column(albums::Column::Cover)
+            .column(albums::Column::Images)
+            .into_tuple::<(Option<String>, Json)>()
+            .all(&self.db)
+            .await?;
+
+        for (cover, gallery) in albums {
+            images.extend(cover.map(|file| (albums_images, file)));
+            images.extend(
+                gallery_entries(&gallery)
+                    .into_iter()
+                    .flatten()
+                    .map(|(file, _)| (albums_images, file.to_owned())),
+            );
+        }
+
+        let authors_images = image_directory("authors").unwrap();
+        let authors = PostsAuthors::find()
+            .select_only()
+            .column(posts_authors::Column::Image)
+            .into_tuple::<Option<String>>()
+            .all(&self.db)
+            .await?;
+
+        images.extend(
+            authors
+                .into_iter()
+                .flatten()
+                .map(|file| (authors_images, file)),
+        );
+
+        let colleagues_images = image_directory("colleagues").unwrap();
+        let colleagues = ColleaguesData::find()
+            .select_only()
+            .column(colleagues_data::Column::Image)
+            .into_tuple::<Option<String>>()
+            .all(&self.db)
+            .await?;
+
+        images.extend(
+            colleagues
+                .into_iter()
+                .flatten()
+                .map(|file| (colleagues_images, file)),
+        );
+
+        images.retain(|(_, file)| !file.is_empty());
+
+        Ok(images)
+    }
+
+    async fn run(&mut self) -> Result<(), DbErr> {
+        let known: HashMap<(String, String), DateTime> = ImagePlaceholders::find()
+            .select_only()
+            .column(image_placeholders::Column::Directory)
+            .column(image_placeholders::Column::File)
+            .column(image_placeholders::Column::ModifiedAt)
+            .into_tuple::<(String, String, DateTime)>()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .map(|(directory, file, modified_at)| ((directory, file), modified_at))
+            .collect();
+
+        let mut updated = 0;
+
+        for (directory, file) in self.referenced_images().await? {
+            let modified_at = match self.storage.modified_at(directory, &file).await {
+                // DATETIME columns have no fractional seconds.
+                Ok(Some(modified_at)) => modified_at.naive_utc().with_nanosecond(0).unwrap(),
+                Ok(None) => continue,
+                Err(err) => {
+                    tracing::warn!("Could not read image {directory}/{file}: {err}");
+                    continue;
+                }
+            };
+
+            if known.get(&(directory.to_owned(), file.clone())) == Some(&modified_at)
+                || self.failed.get(&(directory, file.clone())) == Some(&modified_at)
+            {
+                continue;
+            }
+
+            let placeholder = match self.storage.get(directory, &file).await {
+                Ok(Some(original)) => tokio::task::spawn_blocking(move || compute(&original))
+                    .await
+                    .expect("Placeholder task panicked"),
+                Ok(None) => continue,
+                Err(err) => Err(err.into()),
+            };
+
+            let (blurhash, dominant_color) = match placeholder {
+                Ok(placeholder) => placeholder,
+                Err(err) => {
+                    tracing::warn!("Could not compute placeholder for {directory}/{file}: {err}");
+                    self.failed.insert((directory, file), modified_at);
+                    continue;
+                }
+            };
+
+            self.failed.remove(&(directory, file.clone()));
+
+            let now = Local::now().naive_local();
+
+            let result = ImagePlaceholders::insert(image_placeholders::ActiveModel {
+                directory: Set(directory.to_owned()),
+                file: Set(file.clone()),
+                modified_at: Set(modified_at),
+                blurhash: Set(blurhash),
+                dominant_color: Set(dominant_color),
+                created_at: Set(now),
+                updated_at: Set(now),
+            })
+            .on_conflict(
+                OnConflict::columns([
+                    image_placeholders::Column::Directory,
+                    image_placeholders::Column::File,
+                ])
+                .update_columns([
+                    image_placeholders::Column::ModifiedAt,
+                    image_placeholders::Column::Blurhash,
+                    image_placeholders::Column::DominantColor,
+                    image_placeholders::Column::UpdatedAt,
+                ])
+                .to_owned(),
+            )
+            .exec(&self.db)
+            .await;
+
+            if let Err(err) = result {
+                tracing::error!("Could not save placeholder for {directory}/{file}: {err}");
+                continue;
+            }
+
+            updated += 1;
+        }
+
+        if updated > 0 {
+            tracing::info!("Updated {updated} image placeholders");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,265 @@
+use super::{Storage, StorageError, is_valid_file_name, write_atomically};
+use crate::Config;
+use async_graphql::Enum;
+use image::{
+    DynamicImage, ImageEncoder, ImageError,
+    codecs::{avif::AvifEncoder, jpeg::JpegEncoder, png::PngEncoder},
+    imageops::FilterType,
+};
+use serde::Deserialize;
+use std::{path::PathBuf, sync::Arc};
+
+const JPEG_QUALITY: u8 = 82;
+const WEBP_QUALITY: f32 = 80.0;
+const AVIF_QUALITY: u8 = 70;
+const AVIF_SPEED: u8 = 8;
+
+/// Storage directories that images can be served from, by their kind in `/img` URLs.
+const IMAGE_KINDS: [(&str, &str); 4] = [
+    ("posts", "posts_images"),
+    ("albums", "albums_images"),
+    ("authors", "authors_images"),
+    ("colleagues", "colleagues_images"),
+];
+
+/// How a resized image is fitted into the requested box.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Fit {
+    /// Scale and crop to fill the box exactly.
+    Cover,
+    /// Scale to fit inside the box, keeping the aspect ratio.
+    #[default]
+    Contain,
+    /// Stretch to the box, ignoring the aspect ratio.
+    Fill,
+}
+
+/// Encoding of a resized image.
+#[derive(Enum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VariantFormat {
+    Jpeg,
+    Png,
+    Webp,
+    Avif,
+}
+
+impl VariantFormat {
+    fn from_file_name(file: &str) -> Self {
+        let extension = file
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+
+        match extension.as_deref() {
+            Some("png" | "gif") => Self::Png,
+            Some("webp") => Self::Webp,
+            Some("avif") => Self::Avif,
+            _ => Self::Jpeg,
+        }
+    }
+
+    pub fn mime_type(self) -> &'static str {
+        match self {
+            Self::Jpeg => "image/jpeg",
+            Self::Png => "image/png",
+            Self::Webp => "image/webp",
+            Self::Avif => "image/avif",
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Jpeg => "jpeg",
+            Self::Png => "png",
+            Self::Webp => "webp",
+            Self::Avif => "avif",
+        }
+    }
+}
+
+/// Query parameters of an `/img` request.
+#[derive(Deserialize, Debug)]
+pub struct ResizeOptions {
+    pub w: Option<u32>,
+    pub h: Option<u32>,
+    #[serde(default)]
+    pub fit: Fit,
+    pub format: Option<VariantFormat>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum ResizeError {
+    #[error("Image not found")]
+    NotFound,
+    #[error("Image size not allowed")]
+    SizeNotAllowed,
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+    #[error("Image error: {0}")]
+    Image(#[from] ImageError),
+    #[error("Cache error: {0}")]
+    Cache(#[from] std::io::Error),
+}
+
+/// The storage directory for an image kind.
+pub fn image_directory(kind: &str) -> Option<&'static str> {
+    IMAGE_KINDS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, directory)| *directory)
+}
+
+/// Parse the comma-separated list of allowed image dimensions.
+pub fn image_sizes(config: &Config) -> Vec<u32> {
+    let mut sizes: Vec<u32> = config
+        .image_sizes
+        .split(',')
+        .filter_map(|size| size.trim().parse().ok())
+        .collect();
+
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+}
+
+fn resize(
+    original: &[u8],
+    width: Option<u32>,
+    height: Option<u32>,
+    fit: Fit,
+    format: VariantFormat,
+) -> Result<Vec<u8>, ImageError> {
+    let mut image = image::load_from_memory(original)?;
+
+    if width.is_some() || height.is_some() {
+        let (original_width, original_height) = (image.width(), image.height());
+
+        // A missing dimension follows the aspect ratio, and images are never upscaled.
+        let width = width
+            .unwrap_or_else(|| {
+                (u64::from(original_width) * u64::from(height.unwrap())
+                    / u64::from(original_height)) as u32
+            })
+            .clamp(1, original_width);
+        let height = height
+            .unwrap_or_else(|| {
+                (u64::from(original_height) * u64::from(width) / u64::from(original_width)) as u32
+            })
+            .clamp(1, original_height);
+
+        image = match fit {
+            Fit::Cover => image.resize_to_fill(width, height, FilterType::Lanczos3),
+            Fit::Contain => image.resize(width, height, FilterType::Lanczos3),
+            Fit::Fill => image.resize_exact(width, height, FilterType::Lanczos3),
+        };
+    }
+
+    let mut encoded = Vec::new();
+
+    match format {
+        VariantFormat::Jpeg => {
+            let image = DynamicImage::ImageRgb8(image.to_rgb8());
+            JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY).encode_image(&image)?;
+        }
+        VariantFormat::Png => {
+            let image = image.to_rgba8();
+            PngEncoder::new(&mut encoded).write_image(
+                &image,
+                image.width(),
+                image.height(),
+                image::ExtendedColorType::Rgba8,
+            )?;
+        }
+        VariantFormat::Webp => {
+            let image = image.to_rgba8();
+            encoded = webp::Encoder::from_rgba(&image, image.width(), image.height())
+                .encode(WEBP_QUALITY)
+                .to_vec();
+        }
+        VariantFormat::Avif => {
+            let image = image.to_rgba8();
+            AvifEncoder::new_with_speed_quality(&mut encoded, AVIF_SPEED, AVIF_QUALITY)
+                .write_image(
+                    &image,
+                    image.width(),
+                    image.height(),
+                    image::ExtendedColorType::Rgba8,
+                )?;
+        }
+    }
+
+    Ok(encoded)
+}
+
+/// Produces resized and re-encoded variants of stored images, caching them on disk.
+///
+/// Originals are treated as immutable, so cached variants are never invalidated.
+pub struct ImageResizer {
+    storage: Arc<dyn Storage>,
+    cache_dir: PathBuf,
+    sizes: Vec<u32>,
+}
+
+impl ImageResizer {
+    pub fn new(config: &Config, storage: Arc<dyn Storage>) -> Self {
+        Self {
+            storage,
+            cache_dir: PathBuf::from(&config.image_cache_dir),
+            sizes: image_sizes(config),
+        }
+    }
+
+    fn is_allowed_size(&self, size: Option<u32>) -> bool {
+        size.is_none_or(|size| self.sizes.contains(&size))
+    }
+
+    /// Load the variant of `file` described by `options`, rendering it if it is not cached yet.
+    pub async fn variant(
+        &self,
+        kind: &str,
+        file: &str,
+        options: &ResizeOptions,
+    ) -> Result<(Vec<u8>, VariantFormat), ResizeError> {
+        let Some(directory) = image_directory(kind).filter(|_| is_valid_file_name(file)) else {
+            return Err(ResizeError::NotFound);
+        };
+
+        if !self.is_allowed_size(options.w) || !self.is_allowed_size(options.h) {
+            return Err(ResizeError::SizeNotAllowed);
+        }
+
+        let format = options
+            .format
+            .unwrap_or_else(|| VariantFormat::from_file_name(file));
+
+        let cache_path = self
+            .cache_dir
+            .join(directory)
+            .join(format!(
+                "{}x{}-{:?}",
+                options.w.unwrap_or(0),
+                options.h.unwrap_or(0),
+                options.fit
+            ))
+            .join(format!("{file}.{}", format.as_str()));
+
+        if let Ok(cached) = tokio::fs::read(&cache_path).await {
+            return Ok((cached, format));
+        }
+
+        let Some(original) = self.storage.get(directory, file).await? else {
+            return Err(ResizeError::NotFound);
+        };
+
+        let (width, height, fit) = (options.w, options.h, options.fit);
+        let encoded =
+            tokio::task::spawn_blocking(move || resize(&original, width, height, fit, format))
+                .await
+                .expect("Image resize task panicked")?;
+
+        write_atomically(&cache_path, &encoded).await?;
+
+        Ok((encoded, format))
+    }
+}
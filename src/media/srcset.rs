@@ -0,0 +1,50 @@
+use super::{VariantFormat, image_sizes};
+use crate::Config;
+use percent_encoding::{AsciiSet, CONTROLS, utf8_percent_encode};
+
+/// Characters escaped in a URL path segment.
+const PATH_SEGMENT: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'/')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}');
+
+/// Build a `srcset` attribute value listing resized variants of an image.
+///
+/// Widths outside the allowed image sizes are left out; `None` means all of them.
+/// Returns `None` if no storage is configured.
+pub fn srcset(
+    config: &Config,
+    kind: &str,
+    file: &str,
+    widths: Option<&[u32]>,
+    format: Option<VariantFormat>,
+) -> Option<String> {
+    config.storage_url.as_ref()?;
+
+    let sizes = image_sizes(config);
+    let file = utf8_percent_encode(file, PATH_SEGMENT);
+    let format = format.map_or(String::new(), |format| {
+        format!("&format={}", format.as_str())
+    });
+
+    let candidates: Vec<_> = sizes
+        .iter()
+        .filter(|size| widths.is_none_or(|widths| widths.contains(size)))
+        .map(|width| {
+            format!(
+                "{}/img/{kind}/{file}?w={width}{format} {width}w",
+                config.public_base_url
+            )
+        })
+        .collect();
+
+    Some(candidates.join(", "))
+}
@@ -0,0 +1,279 @@
+use crate::Config;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::{StreamExt, TryStreamExt, stream::BoxStream};
+use object_store::{
+    ObjectStoreExt,
+    aws::{AmazonS3, AmazonS3Builder},
+    path::Path as StorePath,
+};
+use std::{
+    future::Future,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+use tokio_util::io::ReaderStream;
+use url::Url;
+
+pub type StorageFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, StorageError>> + Send + 'a>>;
+
+/// The contents of a file, read in chunks.
+pub type StorageStream = BoxStream<'static, Result<Bytes, StorageError>>;
+
+/// Write a file through a temporary one in the same directory, so readers never see a
+/// partial file.
+///
+/// The temporary file has a unique name, so concurrent writers of the same file never write
+/// into each other's.
+pub async fn write_atomically(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let parent = path.parent().unwrap();
+    let temporary = parent.join(format!(
+        ".{}.{}.{}.tmp",
+        path.file_name().unwrap_or_default().to_string_lossy(),
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    tokio::fs::create_dir_all(parent).await?;
+
+    let written = match tokio::fs::write(&temporary, contents).await {
+        Ok(()) => tokio::fs::rename(&temporary, path).await,
+        Err(err) => Err(err),
+    };
+
+    if written.is_err() {
+        let _ = tokio::fs::remove_file(&temporary).await;
+    }
+
+    written
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum StorageError {
+    #[error("Invalid storage URL: {0}")]
+    InvalidUrl(String),
+    #[error("Invalid file name: {0}")]
+    InvalidFileName(String),
+    #[error("Object store error: {0}")]
+    ObjectStore(#[from] object_store::Error),
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Whether `file` is a plain file name that cannot escape its storage directory.
+pub fn is_valid_file_name(file: &str) -> bool {
+    !file.is_empty() && !file.starts_with('.') && !file.contains(['/', '\\'])
+}
+
+/// Backend holding the files served under `STORAGE_BASE_URL`, in directories such as
+/// `posts_images`.
+pub trait Storage: Send + Sync {
+    /// Read a file, or `None` if it does not exist.
+    fn get<'a>(&'a self, directory: &'a str, file: &'a str) -> StorageFuture<'a, Option<Bytes>>;
+
+    /// Read a file without loading it into memory, or `None` if it does not exist.
+    fn stream<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<StorageStream>>;
+
+    /// Time of the last modification of a file, or `None` if it does not exist.
+    fn modified_at<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<DateTime<Utc>>>;
+
+    /// Write a file, replacing it if it already exists.
+    fn put<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+        contents: Bytes,
+    ) -> StorageFuture<'a, ()>;
+}
+
+/// Open the storage configured by `STORAGE_URL`.
+///
+/// `file://` URLs point to a local directory, and `s3://bucket/prefix` URLs to an
+/// S3-compatible bucket. Returns `None` if no storage is configured.
+pub fn open_storage(config: &Config) -> Result<Option<Arc<dyn Storage>>, StorageError> {
+    let Some(ref source) = config.storage_url else {
+        return Ok(None);
+    };
+
+    let url = Url::parse(source).map_err(|_| StorageError::InvalidUrl(source.clone()))?;
+
+    let storage: Arc<dyn Storage> = match url.scheme() {
+        "file" => Arc::new(LocalStorage::new(
+            url.to_file_path()
+                .map_err(|_| StorageError::InvalidUrl(source.clone()))?,
+        )),
+        "s3" => Arc::new(S3Storage::new(&url)?),
+        _ => return Err(StorageError::InvalidUrl(source.clone())),
+    };
+
+    Ok(Some(storage))
+}
+
+/// Files in a directory on the local filesystem.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, directory: &str, file: &str) -> Result<PathBuf, StorageError> {
+        if !is_valid_file_name(directory) || !is_valid_file_name(file) {
+            return Err(StorageError::InvalidFileName(format!("{directory}/{file}")));
+        }
+
+        Ok(self.root.join(directory).join(file))
+    }
+}
+
+impl Storage for LocalStorage {
+    fn get<'a>(&'a self, directory: &'a str, file: &'a str) -> StorageFuture<'a, Option<Bytes>> {
+        Box::pin(async move {
+            match tokio::fs::read(self.path(directory, file)?).await {
+                Ok(contents) => Ok(Some(contents.into())),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn stream<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<StorageStream>> {
+        Box::pin(async move {
+            match tokio::fs::File::open(self.path(directory, file)?).await {
+                Ok(file) => Ok(Some(ReaderStream::new(file).map_err(Into::into).boxed())),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn modified_at<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<DateTime<Utc>>> {
+        Box::pin(async move {
+            match tokio::fs::metadata(self.path(directory, file)?).await {
+                Ok(metadata) => Ok(Some(metadata.modified()?.into())),
+                Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+        contents: Bytes,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            write_atomically(&self.path(directory, file)?, &contents).await?;
+
+            Ok(())
+        })
+    }
+}
+
+/// Files in an S3-compatible bucket, under an optional prefix.
+///
+/// Credentials and the endpoint are read from the usual `AWS_*` variables, e.g.
+/// `AWS_ENDPOINT` and `AWS_ALLOW_HTTP` for a local MinIO server.
+pub struct S3Storage {
+    store: AmazonS3,
+    prefix: StorePath,
+}
+
+impl S3Storage {
+    pub fn new(url: &Url) -> Result<Self, StorageError> {
+        let store = AmazonS3Builder::from_env().with_url(url.as_str()).build()?;
+        let prefix = StorePath::parse(url.path().trim_start_matches('/'))
+            .map_err(|_| StorageError::InvalidUrl(url.to_string()))?;
+
+        Ok(Self { store, prefix })
+    }
+
+    fn location(&self, directory: &str, file: &str) -> Result<StorePath, StorageError> {
+        if !is_valid_file_name(directory) || !is_valid_file_name(file) {
+            return Err(StorageError::InvalidFileName(format!("{directory}/{file}")));
+        }
+
+        Ok(self.prefix.clone().join(directory).join(file))
+    }
+}
+
+impl Storage for S3Storage {
+    fn get<'a>(&'a self, directory: &'a str, file: &'a str) -> StorageFuture<'a, Option<Bytes>> {
+        Box::pin(async move {
+            match self.store.get(&self.location(directory, file)?).await {
+                Ok(result) => Ok(Some(result.bytes().await?)),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn stream<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<StorageStream>> {
+        Box::pin(async move {
+            match self.store.get(&self.location(directory, file)?).await {
+                Ok(result) => Ok(Some(result.into_stream().map_err(Into::into).boxed())),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn modified_at<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+    ) -> StorageFuture<'a, Option<DateTime<Utc>>> {
+        Box::pin(async move {
+            match self.store.head(&self.location(directory, file)?).await {
+                Ok(meta) => Ok(Some(meta.last_modified)),
+                Err(object_store::Error::NotFound { .. }) => Ok(None),
+                Err(err) => Err(err.into()),
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        directory: &'a str,
+        file: &'a str,
+        contents: Bytes,
+    ) -> StorageFuture<'a, ()> {
+        Box::pin(async move {
+            self.store
+                .put(&self.location(directory, file)?, contents.into())
+                .await?;
+
+            Ok(())
+        })
+    }
+}
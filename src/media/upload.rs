@@ -0,0 +1,311 @@
+use super::{Storage, StorageError, image_directory};
+use image::{
+    DynamicImage, ImageDecoder, ImageError, ImageFormat, ImageReader,
+    codecs::{jpeg::JpegEncoder, png::PngEncoder, webp::WebPEncoder},
+    metadata::Orientation,
+};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+const JPEG_QUALITY: u8 = 90;
+/// Number of hex digits of the content hash used in file names.
+const HASH_LENGTH: usize = 32;
+
+/// Flags in the first byte of the `VP8X` chunk of an extended WebP file.
+const WEBP_EXIF_FLAG: u8 = 0x08;
+const WEBP_XMP_FLAG: u8 = 0x04;
+const WEBP_ANIMATION_FLAG: u8 = 0x02;
+
+/// Image formats accepted for upload.
+const UPLOAD_FORMATS: [ImageFormat; 4] = [
+    ImageFormat::Jpeg,
+    ImageFormat::Png,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+];
+
+#[derive(thiserror::Error, Debug)]
+pub enum UploadError {
+    #[error("Unknown image kind")]
+    UnknownKind,
+    #[error("File is too large")]
+    TooLarge,
+    #[error("Unsupported image format, expected JPEG, PNG, GIF or WebP")]
+    UnsupportedFormat,
+    #[error("Invalid image: {0}")]
+    Image(#[from] ImageError),
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+/// An uploaded image, as written to storage.
+#[derive(Debug)]
+pub struct StoredImage {
+    /// File name in the storage directory of the image kind.
+    pub file: String,
+    pub width: u32,
+    pub height: u32,
+    pub mime_type: &'static str,
+}
+
+/// The chunks of a WebP file as their FourCC and data, or `None` if it is malformed.
+fn webp_chunks(file: &[u8]) -> Option<Vec<(&[u8], &[u8])>> {
+    if file.get(..4)? != b"RIFF" || file.get(8..12)? != b"WEBP" {
+        return None;
+    }
+
+    let size = u32::from_le_bytes(file[4..8].try_into().unwrap()) as usize;
+    let mut rest = file.get(12..size.checked_add(8)?)?;
+    let mut chunks = Vec::new();
+
+    while !rest.is_empty() {
+        let name = rest.get(..4)?;
+        let length = u32::from_le_bytes(rest.get(4..8)?.try_into().unwrap()) as usize;
+        let end = length.checked_add(8)?;
+
+        chunks.push((name, rest.get(8..end)?));
+        // Chunks are padded to an even length.
+        rest = &rest[(end + length % 2).min(rest.len())..];
+    }
+
+    Some(chunks)
+}
+
+/// Whether a WebP file has more than one frame.
+fn is_animated_webp(file: &[u8]) -> bool {
+    webp_chunks(file).is_some_and(|chunks| {
+        chunks.iter().any(|(name, data)| {
+            *name == b"VP8X"
+                && data
+                    .first()
+                    .is_some_and(|flags| flags & WEBP_ANIMATION_FLAG != 0)
+        })
+    })
+}
+
+/// Remove the `EXIF` and `XMP ` chunks of a WebP file, keeping the image data as is.
+fn strip_webp_metadata(original: &[u8]) -> Option<Vec<u8>> {
+    let mut body = b"WEBP".to_vec();
+
+    for (name, data) in webp_chunks(original)? {
+        if name == b"EXIF" || name == b"XMP " {
+            continue;
+        }
+
+        body.extend_from_slice(name);
+        body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+
+        match data.split_first() {
+            Some((flags, rest)) if name == b"VP8X" => {
+                body.push(flags & !(WEBP_EXIF_FLAG | WEBP_XMP_FLAG));
+                body.extend_from_slice(rest);
+            }
+            _ => body.extend_from_slice(data),
+        }
+
+        if data.len() % 2 == 1 {
+            body.push(0);
+        }
+    }
+
+    let mut file = b"RIFF".to_vec();
+
+    file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+    file.extend_from_slice(&body);
+
+    Some(file)
+}
+
+/// Re-encode an image, dropping EXIF and other metadata.
+///
+/// The EXIF orientation is applied to the pixels first, so the image keeps looking
+/// the same. GIFs carry no EXIF data and are stored as is to keep animations.
+///
+/// WebP images are only re-encoded if they need rotating, as the encoder is lossless and
+/// keeps only the first frame. Otherwise their metadata chunks are removed.
+fn strip_metadata(original: &[u8], format: ImageFormat) -> Result<Vec<u8>, ImageError> {
+    if format == ImageFormat::Gif {
+        return Ok(original.to_vec());
+    }
+
+    let mut decoder = ImageReader::with_format(Cursor::new(original), format).into_decoder()?;
+    let orientation = decoder.orientation()?;
+    let mut image = DynamicImage::from_decoder(decoder)?;
+
+    if format == ImageFormat::WebP
+        && (orientation == Orientation::NoTransforms || is_animated_webp(original))
+        && let Some(stripped) = strip_webp_metadata(original)
+    {
+        return Ok(stripped);
+    }
+
+    image.apply_orientation(orientation);
+
+    let mut encoded = Vec::new();
+
+    match format {
+        ImageFormat::Jpeg => JpegEncoder::new_with_quality(&mut encoded, JPEG_QUALITY)
+            .encode_image(&DynamicImage::ImageRgb8(image.to_rgb8()))?,
+        ImageFormat::Png => image.write_with_encoder(PngEncoder::new(&mut encoded))?,
+        _ => DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_with_encoder(WebPEncoder::new_lossless(&mut encoded))?,
+    }
+
+    Ok(encoded)
+}
+
+/// Validate and clean up an uploaded image.
+///
+/// The format is detected from the contents, not from the name or the declared MIME type.
+fn process(original: &[u8]) -> Result<(Vec<u8>, ImageFormat, u32, u32), UploadError> {
+    let format = image::guess_format(original)
+        .ok()
+        .filter(|format| UPLOAD_FORMATS.contains(format))
+        .ok_or(UploadError::UnsupportedFormat)?;
+
+    let encoded = strip_metadata(original, format)?;
+    let (width, height) =
+        ImageReader::with_format(Cursor::new(&encoded), format).into_dimensions()?;
+
+    Ok((encoded, format, width, height))
+}
+
+/// File name derived from the contents, so identical uploads share a file.
+fn content_hashed_name(contents: &[u8], format: ImageFormat) -> String {
+    let hash: String = Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    format!("{}.{}", &hash[..HASH_LENGTH], format.extensions_str()[0])
+}
+
+/// Validate an uploaded image, strip its metadata and write it to the storage directory
+/// of `kind`.
+pub async fn store_upload(
+    storage: &dyn Storage,
+    kind: &str,
+    original: Vec<u8>,
+    max_size: u64,
+) -> Result<StoredImage, UploadError> {
+    let directory = image_directory(kind).ok_or(UploadError::UnknownKind)?;
+
+    if original.len() as u64 > max_size {
+        return Err(UploadError::TooLarge);
+    }
+
+    let (encoded, format, width, height) = tokio::task::spawn_blocking(move || process(&original))
+        .await
+        .expect("Image upload task panicked")?;
+
+    let file = content_hashed_name(&encoded, format);
+
+    storage.put(directory, &file, encoded.into()).await?;
+
+    Ok(StoredImage {
+        file,
+        width,
+        height,
+        mime_type: format.to_mime_type(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    fn chunk(name: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = name.to_vec();
+
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+
+        chunk
+    }
+
+    fn riff(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let body = chunks.concat();
+        let mut file = b"RIFF".to_vec();
+
+        file.extend_from_slice(&(body.len() as u32 + 4).to_le_bytes());
+        file.extend_from_slice(b"WEBP");
+        file.extend_from_slice(&body);
+
+        file
+    }
+
+    /// A lossless WebP with EXIF and XMP chunks, and its image chunk.
+    fn webp_with_metadata(flags: u8) -> (Vec<u8>, Vec<u8>) {
+        let mut simple = Vec::new();
+
+        DynamicImage::ImageRgba8(RgbaImage::from_pixel(3, 2, [200, 100, 50, 255].into()))
+            .write_with_encoder(WebPEncoder::new_lossless(&mut simple))
+            .unwrap();
+
+        let (_, bitstream) = webp_chunks(&simple).unwrap()[0];
+        let image = chunk(b"VP8L", bitstream);
+        let mut vp8x = vec![flags | WEBP_EXIF_FLAG | WEBP_XMP_FLAG, 0, 0, 0];
+
+        vp8x.extend_from_slice(&[2, 0, 0, 1, 0, 0]);
+
+        let file = riff(&[
+            chunk(b"VP8X", &vp8x),
+            image.clone(),
+            chunk(b"EXIF", b"Exif\0\0MM\0*odd"),
+            chunk(b"XMP ", b"<x:xmpmeta/>"),
+        ]);
+
+        (file, image)
+    }
+
+    #[test]
+    fn strips_webp_metadata_chunks() {
+        let (original, image) = webp_with_metadata(0);
+        let stripped = strip_webp_metadata(&original).unwrap();
+        let names: Vec<_> = webp_chunks(&stripped)
+            .unwrap()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(names, [b"VP8X", b"VP8L"]);
+        assert_eq!(stripped[20] & (WEBP_EXIF_FLAG | WEBP_XMP_FLAG), 0);
+        assert!(stripped.ends_with(&image));
+
+        let decoded = image::load_from_memory_with_format(&stripped, ImageFormat::WebP).unwrap();
+
+        assert_eq!((decoded.width(), decoded.height()), (3, 2));
+    }
+
+    #[test]
+    fn keeps_webp_without_reencoding() {
+        let (original, image) = webp_with_metadata(0);
+        let stored = strip_metadata(&original, ImageFormat::WebP).unwrap();
+
+        assert!(stored.ends_with(&image));
+        assert!(!is_animated_webp(&stored));
+    }
+
+    #[test]
+    fn detects_animated_webp() {
+        let (animated, _) = webp_with_metadata(WEBP_ANIMATION_FLAG);
+        let (still, _) = webp_with_metadata(0);
+
+        assert!(is_animated_webp(&animated));
+        assert!(!is_animated_webp(&still));
+    }
+
+    #[test]
+    fn rejects_malformed_webp() {
+        let (original, _) = webp_with_metadata(0);
+
+        assert_eq!(webp_chunks(b"RIFF"), None);
+        assert_eq!(webp_chunks(&original[..original.len() - 3]), None);
+        assert_eq!(strip_webp_metadata(b"RIFF\x04\0\0\0WAVE"), None);
+    }
+}
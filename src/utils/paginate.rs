@@ -1,7 +1,6 @@
 use crate::{
     entity::posts_data::{Column, Entity as PostsData},
-    graphql::{resolvers::Post, types::PostCursor},
-    select_columns_connection,
+    graphql::{Locale, resolvers::Post, types::PostCursor},
     utils::db_error,
 };
 use async_graphql::{
@@ -130,10 +129,7 @@ where
         |after, before, first, last| async move {
             let mut query = build_paginated_posts(after, before, first, last);
 
-            select_columns_connection!(ctx, query, Column);
-            select_columns_connection!(ctx, query, 
-                "author" => Column::AuthorId,
-                "labels" => Column::Id);
+            query = Post::connection_columns(ctx, query);
 
             if let Some(join) = join {
                 query = query.join_rev(JoinType::Join, join);
@@ -149,6 +145,8 @@ where
 
             res.sort_by_key(|p| std::cmp::Reverse(p.date));
 
+            Post::translate(&mut res, db, &Locale::resolve(ctx, None)).await?;
+
             let (min, max) = get_published_posts_min_max_id(db).await?;
 
             let mut connection = get_connection(&res, min, max)?;
@@ -0,0 +1,149 @@
+use crate::{
+    Config,
+    entity::posts_views::{self, Entity as PostsViews},
+};
+use chrono::{Local, NaiveDate};
+use redis::{AsyncCommands, RedisError, RedisResult, aio::ConnectionManager};
+use sea_orm::{
+    DatabaseConnection, DbErr, EntityTrait, Set,
+    sea_query::{Expr, OnConflict},
+};
+use std::{collections::HashMap, time::Duration};
+
+/// Hash of view counts not yet written to MySQL, keyed by `{date}:{post id}`.
+const PENDING_KEY: &str = "views:pending";
+/// Pending counts taken over by a flush, kept until they are written to MySQL.
+const FLUSHING_KEY: &str = "views:flushing";
+/// Held by the instance currently flushing, so counts are written only once.
+const LOCK_KEY: &str = "views:lock";
+
+#[derive(thiserror::Error, Debug)]
+pub enum FlushError {
+    #[error("Redis error: {0}")]
+    Redis(#[from] RedisError),
+    #[error("Database error: {0}")]
+    Database(#[from] DbErr),
+}
+
+/// Counts post views per day in Redis.
+///
+/// Only the post and the day are recorded, nothing about the reader.
+#[derive(Clone)]
+pub struct ViewCounter {
+    manager: ConnectionManager,
+}
+
+impl ViewCounter {
+    pub async fn new(url: &str) -> RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+
+        Ok(Self {
+            manager: ConnectionManager::new(client).await?,
+        })
+    }
+
+    /// Count a view of a post today.
+    pub async fn record(&self, post_id: u32) -> RedisResult<()> {
+        let mut conn = self.manager.clone();
+        let field = format!("{}:{post_id}", Local::now().date_naive());
+
+        conn.hincr(PENDING_KEY, field, 1).await
+    }
+}
+
+fn parse_field(field: &str) -> Option<(NaiveDate, i32)> {
+    let (date, post_id) = field.split_once(':')?;
+
+    Some((date.parse().ok()?, post_id.parse().ok()?))
+}
+
+/// Background job that periodically moves the view counts from Redis to MySQL.
+pub struct ViewFlushJob {
+    counter: ViewCounter,
+    db: DatabaseConnection,
+    interval: Duration,
+}
+
+impl ViewFlushJob {
+    pub fn new(config: &Config, counter: ViewCounter, db: DatabaseConnection) -> Self {
+        Self {
+            counter,
+            db,
+            interval: Duration::from_secs(config.view_flush_interval),
+        }
+    }
+
+    pub fn spawn(self) {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.interval).await;
+
+                if let Err(err) = self.flush().await {
+                    tracing::error!("Could not flush view counts: {err}");
+                }
+            }
+        });
+    }
+
+    async fn flush(&self) -> Result<(), FlushError> {
+        let mut conn = self.counter.manager.clone();
+
+        let locked: Option<String> = redis::cmd("SET")
+            .arg(LOCK_KEY)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(self.interval.as_secs().max(1))
+            .query_async(&mut conn)
+            .await?;
+
+        if locked.is_none() {
+            return Ok(());
+        }
+
+        // Counts left over by a failed flush are written before taking new ones.
+        if !conn.exists(FLUSHING_KEY).await? {
+            if !conn.exists(PENDING_KEY).await? {
+                return Ok(());
+            }
+
+            let () = conn.rename(PENDING_KEY, FLUSHING_KEY).await?;
+        }
+
+        let counts: HashMap<String, i32> = conn.hgetall(FLUSHING_KEY).await?;
+
+        let rows: Vec<_> = counts
+            .iter()
+            .filter_map(|(field, views)| {
+                let (date, post_id) = parse_field(field)?;
+
+                Some(posts_views::ActiveModel {
+                    posts_id: Set(post_id),
+                    date: Set(date),
+                    views: Set(*views),
+                })
+            })
+            .collect();
+
+        if !rows.is_empty() {
+            PostsViews::insert_many(rows)
+                .on_conflict(
+                    OnConflict::columns([posts_views::Column::PostsId, posts_views::Column::Date])
+                        .value(
+                            posts_views::Column::Views,
+                            Expr::col(posts_views::Column::Views)
+                                .add(Expr::cust("VALUES(`views`)")),
+                        )
+                        .to_owned(),
+                )
+                .exec(&self.db)
+                .await?;
+        }
+
+        let () = conn.del(FLUSHING_KEY).await?;
+
+        tracing::info!("Flushed {} view counts", counts.len());
+
+        Ok(())
+    }
+}